@@ -1,3 +1,5 @@
+use crate::iter_ext::IterExt;
+
 #[cfg(test)]
 mod tests {
     use crate::day1::parse_for_elves;
@@ -22,12 +24,10 @@ mod tests {
 pub(crate) fn solve() {
     let input = std::fs::read_to_string("../input/day1.txt").unwrap();
     let per_elves = parse_for_elves(input.as_str());
-    let mut calories_per_elf = per_elves
+    let calories_per_elf = per_elves
         .iter()
         .map(|x1| sum_calories_per_elf(x1.to_vec()))
-        .collect::<Vec<i32>>();
-    calories_per_elf.sort();
-    calories_per_elf.reverse();
+        .top_n(3);
 
     let part_one = calories_per_elf.first().unwrap();
     let part_two = &calories_per_elf[0..3];
@@ -1,9 +1,22 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::str::FromStr;
+use std::sync::OnceLock;
+
+use itertools::Itertools;
 
 use crate::day2::Shape::{Paper, Rock, Scissors};
 
 pub(crate) fn solve() {
     let input = std::fs::read_to_string("../input/day2.txt").unwrap();
+    let encoding_path = Path::new("../input/day2-encoding.txt");
+    if encoding_path.exists() {
+        let encoding = Encoding::from_file(encoding_path).unwrap();
+        println!("Custom encoding: {}", encoding.points_for_guide(&input));
+    }
+
     let part_one_points: i32 = input
         .lines()
         .map(Game::part_one_game_from_str)
@@ -19,9 +32,32 @@ pub(crate) fn solve() {
         .map(|g| g.points_for_game())
         .sum();
     println!("Part Two: {}", part_two_points);
+
+    let elf_shapes = input
+        .lines()
+        .filter_map(|line| Game::part_two_game_from_str(line).ok())
+        .map(|game| game.elf_play.shape)
+        .collect::<Vec<_>>();
+    for order in 0..=3 {
+        println!(
+            "Adaptive strategy remembering {} plays: {} (guide: {})",
+            order,
+            adaptive_points(&elf_shapes, order),
+            part_two_points
+        );
+    }
+
+    let interpretations = interpretations(&input);
+    println!(
+        "Best of {} interpretations of my column:",
+        interpretations.len()
+    );
+    for interpretation in interpretations.iter().take(3) {
+        println!("  {interpretation}");
+    }
 }
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 enum Shape {
     Rock,
     Paper,
@@ -44,81 +80,606 @@ struct Game {
     my_play: MyPlay,
 }
 
+/// How a round ends for me.
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    const ALL: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Loss => "loss",
+            Outcome::Draw => "draw",
+            Outcome::Win => "win",
+        };
+        f.pad(name)
+    }
+}
+
+impl Shape {
+    const ALL: [Shape; 3] = [Rock, Paper, Scissors];
+
+    /// Position of the shape in `CyclicGame::rock_paper_scissors`.
+    fn index(self) -> usize {
+        Shape::ALL.iter().position(|&shape| shape == self).unwrap()
+    }
+}
+
+/// Points awarded for the outcome of a round, on top of the points for the chosen shape.
+#[derive(PartialEq, Debug, Clone, Copy)]
+struct OutcomePoints {
+    loss: i32,
+    draw: i32,
+    win: i32,
+}
+
+/// A game with an odd number of shapes arranged in a cycle, where every shape beats the half of
+/// the other shapes that come right before it.
+#[derive(PartialEq, Debug, Clone)]
+struct CyclicGame {
+    shapes: Vec<String>,
+    shape_points: Vec<i32>,
+    outcome_points: OutcomePoints,
+}
+
+impl CyclicGame {
+    fn new(shapes: &[(&str, i32)], outcome_points: OutcomePoints) -> Result<Self, &'static str> {
+        if shapes.len() < 3 || shapes.len().is_multiple_of(2) {
+            return Err("A cyclic game needs an odd number of shapes, at least three");
+        }
+        let names = shapes
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        if names.iter().collect::<HashSet<_>>().len() != names.len() {
+            return Err("Shape names must be distinct");
+        }
+        Ok(CyclicGame {
+            shapes: names,
+            shape_points: shapes.iter().map(|&(_, points)| points).collect(),
+            outcome_points,
+        })
+    }
+
+    /// Today's puzzle. Built once, as every round is scored with it.
+    fn rock_paper_scissors() -> &'static Self {
+        static GAME: OnceLock<CyclicGame> = OnceLock::new();
+        GAME.get_or_init(|| {
+            CyclicGame::new(
+                &[("Rock", 1), ("Paper", 2), ("Scissors", 3)],
+                OutcomePoints {
+                    loss: 0,
+                    draw: 3,
+                    win: 6,
+                },
+            )
+            .unwrap()
+        })
+    }
+
+    fn rock_paper_scissors_lizard_spock() -> Self {
+        CyclicGame::new(
+            &[
+                ("Rock", 1),
+                ("Spock", 5),
+                ("Paper", 2),
+                ("Lizard", 4),
+                ("Scissors", 3),
+            ],
+            OutcomePoints {
+                loss: 0,
+                draw: 3,
+                win: 6,
+            },
+        )
+        .unwrap()
+    }
+
+    fn shape(&self, name: &str) -> Option<usize> {
+        self.shapes.iter().position(|shape| shape == name)
+    }
+
+    /// How a round ends for `mine` against `theirs`.
+    fn outcome(&self, mine: usize, theirs: usize) -> Outcome {
+        let distance = (mine + self.shapes.len() - theirs) % self.shapes.len();
+        if distance == 0 {
+            Outcome::Draw
+        } else if distance <= self.shapes.len() / 2 {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    fn points(&self, mine: usize, theirs: usize) -> i32 {
+        let outcome_points = match self.outcome(mine, theirs) {
+            Outcome::Loss => self.outcome_points.loss,
+            Outcome::Draw => self.outcome_points.draw,
+            Outcome::Win => self.outcome_points.win,
+        };
+        outcome_points + self.shape_points[mine]
+    }
+
+    /// A shape that leads to the wanted outcome against `theirs`.
+    fn shape_for(&self, theirs: usize, outcome: Outcome) -> usize {
+        let n = self.shapes.len();
+        match outcome {
+            Outcome::Loss => (theirs + n - 1) % n,
+            Outcome::Draw => theirs,
+            Outcome::Win => (theirs + 1) % n,
+        }
+    }
+}
+
 impl Game {
+    fn outcome(&self) -> Outcome {
+        CyclicGame::rock_paper_scissors()
+            .outcome(self.my_play.shape.index(), self.elf_play.shape.index())
+    }
+
     pub(crate) fn points_for_game(&self) -> i32 {
-        let points_for_game = match (&self.my_play.shape, &self.elf_play.shape) {
-            (Rock, Paper) => 0,
-            (Rock, Scissors) => 6,
-            (Paper, Rock) => 6,
-            (Paper, Scissors) => 0,
-            (Scissors, Rock) => 0,
-            (Scissors, Paper) => 6,
-            _ => 3,
-        };
-        points_for_game + self.my_play.points_for_shape()
+        CyclicGame::rock_paper_scissors()
+            .points(self.my_play.shape.index(), self.elf_play.shape.index())
     }
 
     fn part_one_game_from_str(s: &str) -> Result<Self, &'static str> {
-        if s.len() != 3 {
-            return Err("Given string has incorrect length");
-        }
-        let vec = s.split(' ').collect::<Vec<_>>();
-        let elf_play = ElfPlay::from_str(vec[0]).unwrap();
-        let my_play = MyPlay::from_str(vec[1]).unwrap();
-        Ok(Game { elf_play, my_play })
+        Encoding::part_one().game(s)
     }
 
     fn part_two_game_from_str(s: &str) -> Result<Self, &'static str> {
-        if s.len() != 3 {
-            return Err("Given string has incorrect length");
+        Encoding::part_two().game(s)
+    }
+}
+
+/// What a letter of a strategy guide stands for.
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Meaning {
+    Shape(Shape),
+    /// How the round should end.
+    Outcome(Outcome),
+}
+
+impl FromStr for Meaning {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Rock" => Ok(Meaning::Shape(Rock)),
+            "Paper" => Ok(Meaning::Shape(Paper)),
+            "Scissors" => Ok(Meaning::Shape(Scissors)),
+            "lose" => Ok(Meaning::Outcome(Outcome::Loss)),
+            "draw" => Ok(Meaning::Outcome(Outcome::Draw)),
+            "win" => Ok(Meaning::Outcome(Outcome::Win)),
+            _ => Err("Unknown meaning, expected a shape or one of lose, draw and win"),
         }
-        let vec = s.split_whitespace().collect::<Vec<_>>();
-        let elf_play = ElfPlay::from_str(vec[0]).unwrap();
-        let my_play = Game::my_play_for(elf_play.shape, vec[1]).unwrap();
+    }
+}
+
+/// The letters used in each column of a strategy guide. The elf's column always names shapes, my
+/// column names either shapes or how the round should end.
+#[derive(PartialEq, Debug, Clone)]
+struct Encoding {
+    elf: HashMap<char, Shape>,
+    mine: HashMap<char, Meaning>,
+}
+
+impl Encoding {
+    /// How part one reads the guide: X, Y and Z are shapes.
+    fn part_one() -> &'static Self {
+        static ENCODING: OnceLock<Encoding> = OnceLock::new();
+        ENCODING.get_or_init(|| Encoding {
+            elf: HashMap::from([('A', Rock), ('B', Paper), ('C', Scissors)]),
+            mine: HashMap::from([
+                ('X', Meaning::Shape(Rock)),
+                ('Y', Meaning::Shape(Paper)),
+                ('Z', Meaning::Shape(Scissors)),
+            ]),
+        })
+    }
+
+    /// How part two reads the guide: X, Y and Z say how the round ends.
+    fn part_two() -> &'static Self {
+        static ENCODING: OnceLock<Encoding> = OnceLock::new();
+        ENCODING.get_or_init(|| Encoding {
+            mine: HashMap::from([
+                ('X', Meaning::Outcome(Outcome::Loss)),
+                ('Y', Meaning::Outcome(Outcome::Draw)),
+                ('Z', Meaning::Outcome(Outcome::Win)),
+            ]),
+            ..Encoding::part_one().clone()
+        })
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Encoding::from_str(&content)
+    }
+
+    fn elf_play(&self, letter: &str) -> Result<ElfPlay, &'static str> {
+        let shape = single_letter(letter)
+            .and_then(|letter| self.elf.get(&letter))
+            .ok_or("Unknown letter in the elf's column")?;
+        Ok(ElfPlay { shape: *shape })
+    }
+
+    fn my_play(&self, elf_shape: Shape, letter: &str) -> Result<MyPlay, &'static str> {
+        let meaning = single_letter(letter)
+            .and_then(|letter| self.mine.get(&letter))
+            .ok_or("Can't deduce MyPlay for given opposing shape and strategy")?;
+        let shape = match *meaning {
+            Meaning::Shape(shape) => shape,
+            Meaning::Outcome(outcome) => {
+                let game = CyclicGame::rock_paper_scissors();
+                Shape::ALL[game.shape_for(elf_shape.index(), outcome)]
+            }
+        };
+        Ok(MyPlay { shape })
+    }
+
+    /// Total points of every round in the guide that this encoding can read.
+    fn points_for_guide(&self, guide: &str) -> i32 {
+        guide
+            .lines()
+            .map(|line| self.game(line))
+            .filter_map(|r| r.ok())
+            .map(|g| g.points_for_game())
+            .sum()
+    }
+
+    /// Reads a round like `A Y`.
+    fn game(&self, s: &str) -> Result<Game, &'static str> {
+        let mut letters = s.split_whitespace();
+        let (Some(elf), Some(mine), None) = (letters.next(), letters.next(), letters.next()) else {
+            return Err("A round needs exactly two letters");
+        };
+        let elf_play = self.elf_play(elf)?;
+        let my_play = self.my_play(elf_play.shape, mine)?;
         Ok(Game { elf_play, my_play })
     }
+}
 
-    fn my_play_for(elf_shape: Shape, strategy: &str) -> Result<MyPlay, &'static str> {
-        match strategy {
-            "Y" => Ok(MyPlay { shape: elf_shape }),
-            "X" => {
-                let my_shape = match elf_shape {
-                    Rock => Scissors,
-                    Paper => Rock,
-                    Scissors => Paper,
-                };
-                Ok(MyPlay { shape: my_shape })
+/// Reads lines like `elf A Rock` or `me X lose`. Empty lines and lines starting with `#` are
+/// skipped.
+impl FromStr for Encoding {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut encoding = Encoding {
+            elf: HashMap::new(),
+            mine: HashMap::new(),
+        };
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("Line {}: {}", index + 1, message);
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            let [column, letter, meaning] = parts[..] else {
+                return Err(error("expected '<column> <letter> <meaning>'"));
+            };
+            let letter =
+                single_letter(letter).ok_or_else(|| error("letter must be a single character"))?;
+            let meaning = Meaning::from_str(meaning).map_err(error)?;
+            let duplicate = match (column, meaning) {
+                ("elf", Meaning::Shape(shape)) => encoding.elf.insert(letter, shape).is_some(),
+                ("elf", Meaning::Outcome(_)) => {
+                    return Err(error("the elf's column can only name shapes"))
+                }
+                ("me", meaning) => encoding.mine.insert(letter, meaning).is_some(),
+                _ => return Err(error("column must be 'elf' or 'me'")),
+            };
+            if duplicate {
+                return Err(error("letter is already used in this column"));
             }
-            "Z" => {
-                let my_shape = match elf_shape {
-                    Rock => Paper,
-                    Paper => Scissors,
-                    Scissors => Rock,
-                };
-                Ok(MyPlay { shape: my_shape })
+        }
+        Ok(encoding)
+    }
+}
+
+impl Display for Meaning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Meaning::Shape(shape) => write!(f, "{:?}", shape),
+            Meaning::Outcome(Outcome::Loss) => write!(f, "lose"),
+            Meaning::Outcome(Outcome::Draw) => write!(f, "draw"),
+            Meaning::Outcome(Outcome::Win) => write!(f, "win"),
+        }
+    }
+}
+
+/// One way to read my column, with the points the guide would earn that way.
+#[derive(PartialEq, Debug)]
+struct Interpretation {
+    encoding: Encoding,
+    points: i32,
+}
+
+impl Display for Interpretation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (letter, meaning) in self
+            .encoding
+            .mine
+            .iter()
+            .sorted_by_key(|(&letter, _)| letter)
+        {
+            write!(f, "{letter}={meaning} ")?;
+        }
+        write!(f, "-> {}", self.points)
+    }
+}
+
+/// Scores the guide under every assignment of distinct shapes to the letters of my column, and
+/// under every assignment of distinct outcomes to them, best first. The elf's column keeps its
+/// usual letters.
+fn interpretations(guide: &str) -> Vec<Interpretation> {
+    let letters = guide
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter_map(single_letter)
+        .unique()
+        .sorted()
+        .collect::<Vec<_>>();
+    let by_shape = Shape::ALL
+        .into_iter()
+        .permutations(letters.len())
+        .map(|shapes| shapes.into_iter().map(Meaning::Shape).collect::<Vec<_>>());
+    let by_outcome = Outcome::ALL
+        .into_iter()
+        .permutations(letters.len())
+        .map(|outcomes| {
+            outcomes
+                .into_iter()
+                .map(Meaning::Outcome)
+                .collect::<Vec<_>>()
+        });
+
+    let mut interpretations = by_shape
+        .chain(by_outcome)
+        .map(|meanings| {
+            let encoding = Encoding {
+                mine: letters.iter().copied().zip(meanings).collect(),
+                elf: Encoding::part_one().elf.clone(),
+            };
+            let points = encoding.points_for_guide(guide);
+            Interpretation { encoding, points }
+        })
+        .collect::<Vec<_>>();
+    interpretations.sort_by_key(|interpretation| -interpretation.points);
+    interpretations
+}
+
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let round = format!("{:?} vs {:?}", self.my_play.shape, self.elf_play.shape);
+        write!(
+            f,
+            "{:<20} {:<4} {:>2}",
+            round,
+            self.outcome(),
+            self.points_for_game()
+        )
+    }
+}
+
+/// Losses, draws and wins for each of my shapes.
+#[derive(PartialEq, Debug, Default)]
+struct OutcomeStatistics {
+    /// Indexed by `Shape::index` and then by `Outcome`.
+    counts: [[usize; 3]; 3],
+}
+
+impl OutcomeStatistics {
+    fn from_games<'a>(games: impl IntoIterator<Item = &'a Game>) -> Self {
+        let mut statistics = OutcomeStatistics::default();
+        for game in games {
+            statistics.counts[game.my_play.shape.index()][game.outcome() as usize] += 1;
+        }
+        statistics
+    }
+
+    fn count(&self, shape: Shape, outcome: Outcome) -> usize {
+        self.counts[shape.index()][outcome as usize]
+    }
+}
+
+impl Display for OutcomeStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<8}", "")?;
+        for outcome in Outcome::ALL {
+            write!(f, " {:>5}", outcome)?;
+        }
+        for shape in Shape::ALL {
+            write!(f, "\n{:<8}", format!("{:?}", shape))?;
+            for outcome in Outcome::ALL {
+                write!(f, " {:>5}", self.count(shape, outcome))?;
             }
-            _ => Err("Can't deduce MyPlay for given opposing shape and strategy"),
         }
+        Ok(())
+    }
+}
+
+/// A round of the guide as read by part one and by part two.
+#[derive(PartialEq, Debug)]
+struct RoundComparison {
+    line: usize,
+    part_one: Game,
+    part_two: Game,
+}
+
+impl RoundComparison {
+    fn point_difference(&self) -> i32 {
+        self.part_two.points_for_game() - self.part_one.points_for_game()
+    }
+}
+
+impl Display for RoundComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>5}: {} | {} | {:+}",
+            self.line,
+            self.part_one,
+            self.part_two,
+            self.point_difference()
+        )
+    }
+}
+
+/// Reads every round of the guide both ways, skipping rounds that can't be read.
+fn compare_rounds(guide: &str) -> Vec<RoundComparison> {
+    guide
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            Some(RoundComparison {
+                line: index + 1,
+                part_one: Game::part_one_game_from_str(line).ok()?,
+                part_two: Game::part_two_game_from_str(line).ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Explains every round of the guide under both readings and how my shapes fared.
+pub(crate) fn report() {
+    let input = std::fs::read_to_string("../input/day2.txt").unwrap();
+    let rounds = compare_rounds(&input);
+    println!(
+        "{:>5}: {:<28} | {:<28} | Difference",
+        "Line", "Part One", "Part Two"
+    );
+    for round in &rounds {
+        println!("{round}");
+    }
+    println!(
+        "Rounds played differently: {}",
+        rounds
+            .iter()
+            .filter(|round| round.part_one.my_play != round.part_two.my_play)
+            .count()
+    );
+    println!(
+        "Part One outcomes:\n{}",
+        OutcomeStatistics::from_games(rounds.iter().map(|round| &round.part_one))
+    );
+    println!(
+        "Part Two outcomes:\n{}",
+        OutcomeStatistics::from_games(rounds.iter().map(|round| &round.part_two))
+    );
+}
+
+/// Predicts the elf's next shape from the `order` shapes it played right before. Falls back to
+/// shorter histories while a longer one hasn't been seen yet, so order 0 just counts frequencies.
+struct MarkovModel {
+    order: usize,
+    history: VecDeque<Shape>,
+    /// How often each shape followed a history, by history length.
+    counts: Vec<HashMap<Vec<Shape>, [usize; 3]>>,
+}
+
+impl MarkovModel {
+    fn new(order: usize) -> Self {
+        MarkovModel {
+            order,
+            history: VecDeque::with_capacity(order + 1),
+            counts: vec![HashMap::new(); order + 1],
+        }
+    }
+
+    fn context(&self, length: usize) -> Vec<Shape> {
+        self.history
+            .iter()
+            .skip(self.history.len() - length)
+            .copied()
+            .collect()
+    }
+
+    /// How often each shape, by `Shape::index`, followed the longest known recent history.
+    fn predict(&self) -> [usize; 3] {
+        (0..=self.history.len())
+            .rev()
+            .find_map(|length| self.counts[length].get(&self.context(length)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn observe(&mut self, shape: Shape) {
+        for length in 0..=self.history.len() {
+            let context = self.context(length);
+            self.counts[length].entry(context).or_insert([0; 3])[shape.index()] += 1;
+        }
+        self.history.push_back(shape);
+        if self.history.len() > self.order {
+            self.history.pop_front();
+        }
+    }
+}
+
+/// The shape with the most expected points against the predicted shape counts.
+fn best_response(prediction: &[usize; 3]) -> MyPlay {
+    let game = CyclicGame::rock_paper_scissors();
+    let expected_points = |mine: usize| -> usize {
+        (0..3)
+            .map(|theirs| prediction[theirs] * game.points(mine, theirs) as usize)
+            .sum()
+    };
+    let mine = (0..3).max_by_key(|&mine| expected_points(mine)).unwrap();
+    MyPlay {
+        shape: Shape::ALL[mine],
+    }
+}
+
+/// My answer to every round, each chosen only from the elf's plays before it.
+fn best_responses(elf_shapes: &[Shape], order: usize) -> Vec<MyPlay> {
+    let mut model = MarkovModel::new(order);
+    elf_shapes
+        .iter()
+        .map(|&shape| {
+            let my_play = best_response(&model.predict());
+            model.observe(shape);
+            my_play
+        })
+        .collect()
+}
+
+fn adaptive_points(elf_shapes: &[Shape], order: usize) -> i32 {
+    elf_shapes
+        .iter()
+        .zip(best_responses(elf_shapes, order))
+        .map(|(&shape, my_play)| Game {
+            elf_play: ElfPlay { shape },
+            my_play,
+        })
+        .map(|game| game.points_for_game())
+        .sum()
+}
+
+fn single_letter(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => Some(letter),
+        _ => None,
     }
 }
 
 impl MyPlay {
     fn points_for_shape(&self) -> i32 {
-        match self.shape {
-            Rock => 1,
-            Paper => 2,
-            Scissors => 3,
-        }
+        CyclicGame::rock_paper_scissors().shape_points[self.shape.index()]
     }
 }
 
 impl FromStr for MyPlay {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "X" => Ok(MyPlay { shape: Rock }),
-            "Y" => Ok(MyPlay { shape: Paper }),
-            "Z" => Ok(MyPlay { shape: Scissors }),
+        let letter = single_letter(s).ok_or(())?;
+        match Encoding::part_one().mine.get(&letter) {
+            Some(&Meaning::Shape(shape)) => Ok(MyPlay { shape }),
             _ => Err(()),
         }
     }
@@ -127,12 +688,7 @@ impl FromStr for MyPlay {
 impl FromStr for ElfPlay {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" => Ok(ElfPlay { shape: Rock }),
-            "B" => Ok(ElfPlay { shape: Paper }),
-            "C" => Ok(ElfPlay { shape: Scissors }),
-            _ => Err(()),
-        }
+        Encoding::part_one().elf_play(s).map_err(|_| ())
     }
 }
 
@@ -205,3 +761,175 @@ fn points_for_my_play() {
     let scissors = MyPlay { shape: Scissors };
     assert_eq!(3, scissors.points_for_shape());
 }
+
+#[test]
+fn rock_paper_scissors_preset_keeps_the_puzzle_rules() {
+    let game = CyclicGame::rock_paper_scissors();
+    let rock = game.shape("Rock").unwrap();
+    let paper = game.shape("Paper").unwrap();
+    let scissors = game.shape("Scissors").unwrap();
+    assert_eq!(8, game.points(paper, rock));
+    assert_eq!(1, game.points(rock, paper));
+    assert_eq!(6, game.points(scissors, scissors));
+    assert_eq!(7, game.points(rock, scissors));
+    assert_eq!(scissors, game.shape_for(rock, Outcome::Loss));
+    assert_eq!(rock, game.shape_for(scissors, Outcome::Win));
+}
+
+#[test]
+fn every_lizard_spock_shape_beats_exactly_two_others() {
+    let game = CyclicGame::rock_paper_scissors_lizard_spock();
+    let spock = game.shape("Spock").unwrap();
+    let lizard = game.shape("Lizard").unwrap();
+    let scissors = game.shape("Scissors").unwrap();
+    assert_eq!(Outcome::Win, game.outcome(spock, scissors));
+    assert_eq!(Outcome::Win, game.outcome(lizard, spock));
+    assert_eq!(Outcome::Loss, game.outcome(scissors, spock));
+
+    for mine in 0..5 {
+        let wins = (0..5)
+            .filter(|&theirs| game.outcome(mine, theirs) == Outcome::Win)
+            .count();
+        assert_eq!(2, wins);
+        for outcome in Outcome::ALL {
+            assert_eq!(outcome, game.outcome(game.shape_for(mine, outcome), mine));
+        }
+    }
+}
+
+#[test]
+fn rejects_invalid_cyclic_games() {
+    let points = OutcomePoints {
+        loss: 0,
+        draw: 1,
+        win: 2,
+    };
+    assert!(CyclicGame::new(&[("Rock", 1), ("Paper", 2)], points).is_err());
+    assert!(CyclicGame::new(&[("Rock", 1), ("Rock", 2), ("Paper", 3)], points).is_err());
+    // With a single shape there is nothing to win or lose against.
+    assert!(CyclicGame::new(&[("Stone", 1)], points).is_err());
+    assert!(CyclicGame::new(&[], points).is_err());
+}
+
+#[test]
+fn reads_games_with_custom_encoding() {
+    let encoding = Encoding::from_str(
+        "# Letters from the second guide\n\
+         elf R Rock\nelf P Paper\nelf S Scissors\n\n\
+         me 1 lose\nme 2 draw\nme 3 win\nme r Rock\n",
+    )
+    .unwrap();
+    let game = encoding.game("P 3").unwrap();
+    assert_eq!(MyPlay { shape: Scissors }, game.my_play);
+    let game = encoding.game("S r").unwrap();
+    assert_eq!(7, game.points_for_game());
+    assert!(encoding.game("A 1").is_err());
+    assert!(encoding.game("R").is_err());
+}
+
+#[test]
+fn reports_invalid_encodings_by_line() {
+    let error = Encoding::from_str("elf A Rock\nelf B win").unwrap_err();
+    assert_eq!("Line 2: the elf's column can only name shapes", error);
+    let error = Encoding::from_str("me X Rock\nme X Paper").unwrap_err();
+    assert_eq!("Line 2: letter is already used in this column", error);
+    assert!(Encoding::from_str("you X Rock").is_err());
+    assert!(Encoding::from_str("me XY Rock").is_err());
+    assert!(Encoding::from_str("me X Spock").is_err());
+}
+
+#[test]
+fn ranks_every_interpretation_of_my_column() {
+    let guide = "A Y\nB X\nC Z\n";
+    let interpretations = interpretations(guide);
+    assert_eq!(6 + 6, interpretations.len());
+    assert!(interpretations
+        .windows(2)
+        .all(|pair| pair[0].points >= pair[1].points));
+
+    let part_one = Encoding::part_one().points_for_guide(guide);
+    let part_two = Encoding::part_two().points_for_guide(guide);
+    assert_eq!(15, part_one);
+    assert_eq!(12, part_two);
+    for encoding in [Encoding::part_one(), Encoding::part_two()] {
+        assert!(interpretations
+            .iter()
+            .any(|interpretation| interpretation.encoding == *encoding));
+    }
+
+    let ranked = interpretations
+        .iter()
+        .map(|interpretation| interpretation.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!("X=Scissors Y=Paper Z=Rock -> 24", ranked[0]);
+    assert!(ranked.contains(&"X=lose Y=draw Z=win -> 12".to_string()));
+}
+
+#[test]
+fn exposes_the_outcome_of_a_game() {
+    assert_eq!(
+        Outcome::Win,
+        Game::part_one_game_from_str("A Y").unwrap().outcome()
+    );
+    assert_eq!(
+        Outcome::Loss,
+        Game::part_one_game_from_str("B X").unwrap().outcome()
+    );
+    assert_eq!(
+        Outcome::Draw,
+        Game::part_one_game_from_str("C Z").unwrap().outcome()
+    );
+    assert_eq!(
+        Outcome::Win,
+        Game::part_two_game_from_str("C Z").unwrap().outcome()
+    );
+}
+
+#[test]
+fn compares_both_readings_round_by_round() {
+    let rounds = compare_rounds("A Y\nB X\nC Z\n");
+    assert_eq!(3, rounds.len());
+    assert_eq!(-4, rounds[0].point_difference());
+    assert_eq!(0, rounds[1].point_difference());
+    assert_eq!(1, rounds[2].point_difference());
+    assert_eq!(
+        "    1: Paper vs Rock        win   8 | Rock vs Rock         draw  4 | -4",
+        rounds[0].to_string()
+    );
+
+    let statistics = OutcomeStatistics::from_games(rounds.iter().map(|round| &round.part_two));
+    assert_eq!(1, statistics.count(Rock, Outcome::Loss));
+    assert_eq!(1, statistics.count(Rock, Outcome::Draw));
+    assert_eq!(1, statistics.count(Rock, Outcome::Win));
+    assert_eq!(0, statistics.count(Paper, Outcome::Win));
+}
+
+#[test]
+fn markov_model_backs_off_to_shorter_histories() {
+    let mut model = MarkovModel::new(2);
+    assert_eq!([0, 0, 0], model.predict());
+    for shape in [Rock, Paper, Rock, Scissors, Rock] {
+        model.observe(shape);
+    }
+    // Nothing has followed Scissors then Rock yet, so this backs off to what followed Rock alone:
+    // Paper once and Scissors once.
+    assert_eq!([0, 1, 1], model.predict());
+    model.observe(Paper);
+    assert_eq!([1, 0, 0], model.predict());
+}
+
+#[test]
+fn best_responses_learn_the_elfs_patterns() {
+    assert_eq!(MyPlay { shape: Paper }, best_response(&[3, 1, 1]));
+    assert_eq!(MyPlay { shape: Rock }, best_response(&[0, 0, 2]));
+
+    let elf_shapes = [Rock, Paper, Scissors].repeat(10);
+    let transitions = best_responses(&elf_shapes, 1);
+    // Once the previous shape has been seen followed by something, the first order model wins.
+    assert!(transitions[4..]
+        .iter()
+        .zip(&elf_shapes[4..])
+        .all(|(my_play, &shape)| my_play.shape == Shape::ALL[(shape.index() + 1) % 3]));
+    assert_eq!(221, adaptive_points(&elf_shapes, 1));
+    assert!(adaptive_points(&elf_shapes, 0) < 221);
+}
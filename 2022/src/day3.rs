@@ -1,56 +1,555 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+const GROUP_SIZE: usize = 3;
+
 pub(crate) fn solve() {
     println!("Day 3");
     let input = std::fs::read_to_string("../input/day3.txt").unwrap();
-    let part_one: u32 = input
-        .lines()
-        .map(find_relevant_char)
-        .map(char_priority)
+    let catalog_path = Path::new("../input/day3-catalog.txt");
+    let catalog = if catalog_path.exists() {
+        ItemCatalog::from_file(catalog_path).unwrap()
+    } else {
+        ItemCatalog::default()
+    };
+    let rucksacks = match validate(&input, GROUP_SIZE, &catalog) {
+        Ok(rucksacks) => rucksacks,
+        Err(errors) => {
+            for error in errors {
+                println!("{error}");
+            }
+            return;
+        }
+    };
+
+    let anomalies = duplicate_reports(&rucksacks, &catalog)
+        .into_iter()
+        .filter(DuplicateReport::is_anomalous)
+        .collect::<Vec<_>>();
+    println!(
+        "Day 3 rucksacks without exactly one shared item type: {}",
+        anomalies.len()
+    );
+    for report in anomalies {
+        println!("  {report}");
+    }
+
+    let part_one: u32 = rucksacks
+        .iter()
+        .map(|rucksack| find_relevant_char(rucksack, &catalog))
+        .map(|item| catalog.priority(item).unwrap())
         .sum();
     println!("Day 3 Part One: {}", part_one);
 
-    let part_two: u32 = input
-        .lines()
-        .collect::<Vec<_>>()
-        .chunks(3)
-        .map(|chunk| find_relevant_char2(chunk.to_vec()))
-        .map(char_priority)
+    let part_two: u32 = rucksacks
+        .chunks(GROUP_SIZE)
+        .map(|chunk| find_relevant_char2(chunk.to_vec(), &catalog))
+        .map(|item| catalog.priority(item).unwrap())
         .sum();
     println!("Day 3 Part Two: {}", part_two);
+
+    match discover_groups(&rucksacks, GROUP_SIZE, &catalog) {
+        Some(groups) => {
+            let badges: u32 = groups
+                .iter()
+                .map(|group| group.iter().map(|&index| rucksacks[index]).collect())
+                .map(|group| find_relevant_char2(group, &catalog))
+                .map(|item| catalog.priority(item).unwrap())
+                .sum();
+            println!(
+                "Day 3 groups discovered regardless of line order: {}, badges: {}",
+                groups.len(),
+                badges
+            );
+        }
+        None => println!("Day 3 rucksacks can't be split into groups with exactly one badge"),
+    }
+}
+
+/// An item type that may show up in rucksacks.
+#[derive(Debug, PartialEq, Clone)]
+struct Item {
+    symbol: char,
+    priority: u32,
+    name: Option<String>,
+}
+
+/// The item types we know of. Each item type gets an index in the order it was listed, which is
+/// its bit in an `ItemSet`.
+#[derive(Debug, PartialEq)]
+struct ItemCatalog {
+    items: Vec<Item>,
+    indices: HashMap<char, usize>,
+}
+
+impl ItemCatalog {
+    fn from_items(items: Vec<Item>) -> Result<Self, String> {
+        let mut indices = HashMap::new();
+        for (index, item) in items.iter().enumerate() {
+            if indices.insert(item.symbol, index).is_some() {
+                return Err(format!("Item {:?} is listed twice", item.symbol));
+            }
+        }
+        Ok(ItemCatalog { items, indices })
+    }
+
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ItemCatalog::from_str(&content)
+    }
+
+    fn contains(&self, symbol: char) -> bool {
+        self.indices.contains_key(&symbol)
+    }
+
+    fn item(&self, symbol: char) -> Option<&Item> {
+        self.indices.get(&symbol).map(|&index| &self.items[index])
+    }
+
+    fn priority(&self, symbol: char) -> Option<u32> {
+        self.item(symbol).map(|item| item.priority)
+    }
+
+    /// All item types in the rucksack. Symbols that aren't part of the catalog are skipped.
+    fn item_set(&self, rucksack: &str) -> ItemSet {
+        let mut set = ItemSet::empty(self.items.len());
+        for symbol in rucksack.chars() {
+            if let Some(&index) = self.indices.get(&symbol) {
+                set.insert(index);
+            }
+        }
+        set
+    }
+
+    /// Item types all given rucksacks have in common.
+    fn common_items(&self, rucksacks: &[&str]) -> ItemSet {
+        rucksacks
+            .iter()
+            .map(|rucksack| self.item_set(rucksack))
+            .fold(ItemSet::full(self.items.len()), |common, set| {
+                common.intersection(&set)
+            })
+    }
+}
+
+/// Today's table: a-z have priorities 1-26 and A-Z have 27-52.
+impl Default for ItemCatalog {
+    fn default() -> Self {
+        let items = ('a'..='z')
+            .chain('A'..='Z')
+            .map(|symbol| Item {
+                symbol,
+                priority: char_priority(symbol),
+                name: None,
+            })
+            .collect();
+        ItemCatalog::from_items(items).unwrap()
+    }
+}
+
+/// One item type per line, as symbol, priority and an optional name separated by whitespace.
+/// Empty lines and lines starting with `#` are ignored.
+impl FromStr for ItemCatalog {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = vec![];
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("Line {}: {}", index + 1, message);
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let mut symbol = parts.next().unwrap().chars();
+            let (Some(symbol), None) = (symbol.next(), symbol.next()) else {
+                return Err(error("symbol must be a single character"));
+            };
+            let priority = parts
+                .next()
+                .and_then(|priority| priority.parse().ok())
+                .ok_or_else(|| error("priority is missing or not a number"))?;
+            let name = parts.next().map(|name| name.trim().to_string());
+            items.push(Item {
+                symbol,
+                priority,
+                name,
+            });
+        }
+        ItemCatalog::from_items(items)
+    }
+}
+
+/// A set of item types, one bit per index in the `ItemCatalog`.
+#[derive(Debug, PartialEq, Clone)]
+struct ItemSet {
+    words: Vec<u64>,
+}
+
+impl ItemSet {
+    fn empty(size: usize) -> Self {
+        ItemSet {
+            words: vec![0; size.div_ceil(64)],
+        }
+    }
+
+    fn full(size: usize) -> Self {
+        let mut set = ItemSet::empty(size);
+        for index in 0..size {
+            set.insert(index);
+        }
+        set
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    fn intersection(&self, other: &ItemSet) -> ItemSet {
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| a & b)
+            .collect();
+        ItemSet { words }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The smallest index in the set.
+    fn first(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .find(|(_, &word)| word != 0)
+            .map(|(i, word)| i * 64 + word.trailing_zeros() as usize)
+    }
+}
+
+/// A problem with a rucksack that would make solving fail, with its 1-based line number.
+#[derive(Debug, PartialEq)]
+enum RucksackError {
+    InvalidItem { line: usize, item: char },
+    OddLength { line: usize, length: usize },
+    NoSharedItem { line: usize },
+    IncompleteGroup { line: usize, size: usize },
+    NoBadge { line: usize },
 }
 
-fn find_relevant_char(s: &str) -> char {
-    let halves = s.split_at(s.len() / 2);
-    for char in halves.0.chars() {
-        if halves.1.contains(char) {
-            return char;
+impl Display for RucksackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RucksackError::InvalidItem { line, item } => {
+                write!(f, "Line {line}: {item:?} is not a valid item")
+            }
+            RucksackError::OddLength { line, length } => write!(
+                f,
+                "Line {line}: {length} items can't be split into two compartments"
+            ),
+            RucksackError::NoSharedItem { line } => {
+                write!(f, "Line {line}: compartments don't share any item")
+            }
+            RucksackError::IncompleteGroup { line, size } => {
+                write!(
+                    f,
+                    "Line {line}: group starting here only has {size} rucksacks"
+                )
+            }
+            RucksackError::NoBadge { line } => {
+                write!(f, "Line {line}: group starting here doesn't share any item")
+            }
         }
     }
-    panic!("Did not find any duplicate char in both string halves")
 }
 
-fn find_relevant_char2(lines: Vec<&str>) -> char {
-    let first = lines[0];
-    let second = lines[1];
-    let third = lines[2];
+/// Checks every rucksack and group before solving, so that `find_relevant_char` and
+/// `find_relevant_char2` can rely on well-formed input. Returns all problems that were found.
+fn validate<'a>(
+    input: &'a str,
+    group_size: usize,
+    catalog: &ItemCatalog,
+) -> Result<Vec<&'a str>, Vec<RucksackError>> {
+    let rucksacks = input.lines().collect::<Vec<_>>();
+    let is_valid = |rucksack: &str| rucksack.chars().all(|c| catalog.contains(c));
+    let mut errors = vec![];
 
+    for (index, rucksack) in rucksacks.iter().enumerate() {
+        let line = index + 1;
+        if !is_valid(rucksack) {
+            let invalid_items = rucksack
+                .chars()
+                .filter(|&c| !catalog.contains(c))
+                .map(|item| RucksackError::InvalidItem { line, item });
+            errors.extend(invalid_items);
+            continue;
+        }
+        let length = rucksack.chars().count();
+        if length % 2 != 0 {
+            errors.push(RucksackError::OddLength { line, length });
+            continue;
+        }
+        let (first, second) = compartments(rucksack);
+        if catalog.common_items(&[first, second]).is_empty() {
+            errors.push(RucksackError::NoSharedItem { line });
+        }
+    }
+
+    for (index, group) in rucksacks.chunks(group_size).enumerate() {
+        let line = index * group_size + 1;
+        if group.len() < group_size {
+            errors.push(RucksackError::IncompleteGroup {
+                line,
+                size: group.len(),
+            });
+        } else if group.iter().all(|rucksack| is_valid(rucksack))
+            && catalog.common_items(group).is_empty()
+        {
+            errors.push(RucksackError::NoBadge { line });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(rucksacks)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Splits a rucksack into halves with the same number of items.
+fn compartments(rucksack: &str) -> (&str, &str) {
+    let half = rucksack.chars().count() / 2;
+    let middle = rucksack
+        .char_indices()
+        .nth(half)
+        .map_or(rucksack.len(), |(i, _)| i);
+    rucksack.split_at(middle)
+}
+
+/// An item type found in both compartments of a rucksack.
+#[derive(Debug, PartialEq)]
+struct SharedItem {
+    symbol: char,
+    first: usize,
+    second: usize,
+}
+
+/// Every item type the compartments of a rucksack share, in catalog order.
+#[derive(Debug, PartialEq)]
+struct DuplicateReport {
+    line: usize,
+    shared: Vec<SharedItem>,
+}
+
+impl DuplicateReport {
+    /// The puzzle assumes exactly one item type ended up in both compartments.
+    fn is_anomalous(&self) -> bool {
+        self.shared.len() != 1
+    }
+}
+
+impl Display for DuplicateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: ", self.line)?;
+        if self.shared.is_empty() {
+            return write!(f, "no shared item types");
+        }
+        let shared = self
+            .shared
+            .iter()
+            .map(|item| format!("{} ({}/{})", item.symbol, item.first, item.second))
+            .collect::<Vec<_>>();
+        write!(f, "{}", shared.join(", "))
+    }
+}
+
+/// Lists the shared item types of every rucksack, with how often each compartment contains them.
+fn duplicate_reports(rucksacks: &[&str], catalog: &ItemCatalog) -> Vec<DuplicateReport> {
+    rucksacks
+        .iter()
+        .enumerate()
+        .map(|(index, rucksack)| DuplicateReport {
+            line: index + 1,
+            shared: shared_items(rucksack, catalog),
+        })
+        .collect()
+}
+
+fn shared_items(rucksack: &str, catalog: &ItemCatalog) -> Vec<SharedItem> {
+    let count = |compartment: &str| {
+        let mut counts = vec![0; catalog.items.len()];
+        for symbol in compartment.chars() {
+            if let Some(&index) = catalog.indices.get(&symbol) {
+                counts[index] += 1;
+            }
+        }
+        counts
+    };
+    let (first, second) = compartments(rucksack);
+    let (first, second) = (count(first), count(second));
     first
-        .chars()
-        .find(|c| second.contains(*c) && third.contains(*c))
-        .unwrap()
+        .iter()
+        .zip(&second)
+        .enumerate()
+        .filter(|(_, (&first, &second))| first > 0 && second > 0)
+        .map(|(index, (&first, &second))| SharedItem {
+            symbol: catalog.items[index].symbol,
+            first,
+            second,
+        })
+        .collect()
+}
+
+fn find_relevant_char(s: &str, catalog: &ItemCatalog) -> char {
+    let (first, second) = compartments(s);
+    find_relevant_char2(vec![first, second], catalog)
+}
+
+/// Finds the item type all given rucksacks have in common, for groups of any size.
+fn find_relevant_char2(lines: Vec<&str>, catalog: &ItemCatalog) -> char {
+    let index = catalog
+        .common_items(&lines)
+        .first()
+        .expect("Did not find any char common to all lines");
+    catalog.items[index].symbol
+}
+
+/// Splits the rucksacks into groups of `group_size`, regardless of their order, so that every group
+/// shares exactly one item type. Returns the rucksack indices of each group, or `None` if there is
+/// no such split.
+fn discover_groups(
+    rucksacks: &[&str],
+    group_size: usize,
+    catalog: &ItemCatalog,
+) -> Option<Vec<Vec<usize>>> {
+    if group_size == 0 || !rucksacks.len().is_multiple_of(group_size) {
+        return None;
+    }
+    let sets = rucksacks
+        .iter()
+        .map(|rucksack| catalog.item_set(rucksack))
+        .collect::<Vec<_>>();
+    let compatible = sets
+        .iter()
+        .map(|a| sets.iter().map(|b| !a.intersection(b).is_empty()).collect())
+        .collect();
+    let mut search = GroupSearch {
+        assigned: vec![false; sets.len()],
+        sets,
+        compatible,
+        group_size,
+        groups: vec![],
+    };
+    search.run().then_some(search.groups)
+}
+
+/// Backtracking state for `discover_groups`.
+struct GroupSearch {
+    sets: Vec<ItemSet>,
+    /// Whether two rucksacks share any item type at all, and so could end up in the same group.
+    compatible: Vec<Vec<bool>>,
+    group_size: usize,
+    assigned: Vec<bool>,
+    groups: Vec<Vec<usize>>,
+}
+
+impl GroupSearch {
+    /// Groups the remaining rucksacks, starting with the one that has the fewest possible partners.
+    fn run(&mut self) -> bool {
+        let unassigned = (0..self.sets.len())
+            .filter(|&index| !self.assigned[index])
+            .collect::<Vec<_>>();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        for &rucksack in &unassigned {
+            let partners = unassigned
+                .iter()
+                .copied()
+                .filter(|&other| other != rucksack && self.compatible[rucksack][other])
+                .collect::<Vec<_>>();
+            if partners.len() < self.group_size - 1 {
+                return false;
+            }
+            if best
+                .as_ref()
+                .is_none_or(|(_, best)| partners.len() < best.len())
+            {
+                best = Some((rucksack, partners));
+            }
+        }
+        let Some((rucksack, partners)) = best else {
+            return true;
+        };
+
+        self.assigned[rucksack] = true;
+        let common = self.sets[rucksack].clone();
+        if self.complete_group(&mut vec![rucksack], common, &partners) {
+            return true;
+        }
+        self.assigned[rucksack] = false;
+        false
+    }
+
+    fn complete_group(
+        &mut self,
+        group: &mut Vec<usize>,
+        common: ItemSet,
+        partners: &[usize],
+    ) -> bool {
+        if group.len() == self.group_size {
+            if common.len() != 1 {
+                return false;
+            }
+            self.groups.push(group.clone());
+            if self.run() {
+                return true;
+            }
+            self.groups.pop();
+            return false;
+        }
+        for (i, &partner) in partners.iter().enumerate() {
+            let common = common.intersection(&self.sets[partner]);
+            if common.is_empty() {
+                continue;
+            }
+            group.push(partner);
+            self.assigned[partner] = true;
+            if self.complete_group(group, common, &partners[i + 1..]) {
+                return true;
+            }
+            group.pop();
+            self.assigned[partner] = false;
+        }
+        false
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day3::find_relevant_char;
+    use std::str::FromStr;
+
+    use crate::day3::{discover_groups, find_relevant_char};
+    use crate::day3::{duplicate_reports, validate, ItemCatalog, RucksackError, SharedItem};
 
     #[test]
     fn finds_relevant_char() {
         let input = "vJrwpWtwJgWrhcsFMMfFFhFp";
-        let char: char = find_relevant_char(input);
+        let char: char = find_relevant_char(input, &ItemCatalog::default());
         assert_eq!('p', char);
 
         let input = "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL";
-        let char: char = find_relevant_char(input);
+        let char: char = find_relevant_char(input, &ItemCatalog::default());
         assert_eq!('L', char);
     }
 
@@ -61,10 +560,194 @@ mod tests {
             "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
             "PmmdzqPrVvPwwTWBwg",
         ];
-        let char = super::find_relevant_char2(input);
+        let char = super::find_relevant_char2(input, &ItemCatalog::default());
         assert_eq!('r', char);
     }
 
+    #[test]
+    fn finds_common_char_for_any_group_size() {
+        let input = vec!["abcX", "Xdef", "gXhi", "jkXl", "mnoX"];
+        assert_eq!(
+            'X',
+            super::find_relevant_char2(input, &ItemCatalog::default())
+        );
+
+        let input = vec!["BB", "aB"];
+        assert_eq!(
+            'B',
+            super::find_relevant_char2(input, &ItemCatalog::default())
+        );
+    }
+
+    #[test]
+    fn builds_item_sets() {
+        let catalog = ItemCatalog::default();
+        let set = catalog.item_set("aca");
+        assert_eq!(vec![0b101], set.words);
+        assert_eq!(Some(0), set.first());
+        assert_eq!(Some(51), catalog.item_set("Z").first());
+        assert!(catalog.item_set("").is_empty());
+    }
+
+    #[test]
+    fn default_catalog_uses_todays_priorities() {
+        let catalog = ItemCatalog::default();
+        for c in ('a'..='z').chain('A'..='Z') {
+            assert_eq!(Some(super::char_priority(c)), catalog.priority(c));
+        }
+        assert_eq!(None, catalog.priority('1'));
+    }
+
+    #[test]
+    fn parses_catalog_with_unicode_items() {
+        let input = "# symbol priority name\n🍎 5 apple\n\n🍌 7 ripe banana\nx 1\n";
+        let catalog = ItemCatalog::from_str(input).unwrap();
+        assert_eq!(Some(7), catalog.priority('🍌'));
+        let banana = catalog.item('🍌').unwrap();
+        assert_eq!(Some("ripe banana".to_string()), banana.name);
+        assert_eq!(None, catalog.item('x').unwrap().name);
+
+        assert_eq!('🍌', find_relevant_char("x🍌🍌🍎", &catalog));
+        let errors = validate("x🍎a🍎\n", 1, &catalog).unwrap_err();
+        assert_eq!(
+            vec![RucksackError::InvalidItem { line: 1, item: 'a' }],
+            errors
+        );
+    }
+
+    #[test]
+    fn reports_invalid_catalogs() {
+        assert_eq!(
+            Err("Line 2: priority is missing or not a number".to_string()),
+            ItemCatalog::from_str("a 1\nb two")
+        );
+        assert_eq!(
+            Err("Line 1: symbol must be a single character".to_string()),
+            ItemCatalog::from_str("ab 1")
+        );
+        assert_eq!(
+            Err("Item 'a' is listed twice".to_string()),
+            ItemCatalog::from_str("a 1\na 2")
+        );
+    }
+
+    #[test]
+    fn supports_catalogs_with_more_than_64_items() {
+        let symbols = (0..100)
+            .map(|i| char::from_u32(0x100 + i).unwrap())
+            .collect::<Vec<_>>();
+        let input = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| format!("{symbol} {}", i + 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let catalog = ItemCatalog::from_str(&input).unwrap();
+        let rucksack = format!(
+            "{}{}{}{}",
+            symbols[3], symbols[80], symbols[80], symbols[99]
+        );
+        assert_eq!(symbols[80], find_relevant_char(&rucksack, &catalog));
+        assert_eq!(Some(81), catalog.priority(symbols[80]));
+    }
+
+    #[test]
+    fn reports_every_shared_item_with_counts() {
+        let catalog = ItemCatalog::default();
+        let rucksacks = ["vJrwpWtwJgWrhcsFMMfFFhFp", "aAbBcAaa", "abcd"];
+        let reports = duplicate_reports(&rucksacks, &catalog);
+
+        let shared = |symbol, first, second| SharedItem {
+            symbol,
+            first,
+            second,
+        };
+        assert_eq!(vec![shared('p', 1, 1)], reports[0].shared);
+        assert!(!reports[0].is_anomalous());
+        assert_eq!(
+            vec![shared('a', 1, 2), shared('A', 1, 1)],
+            reports[1].shared
+        );
+        assert!(reports[1].is_anomalous());
+        assert!(reports[2].shared.is_empty());
+        assert!(reports[2].is_anomalous());
+
+        assert_eq!("Line 2: a (1/2), A (1/1)", reports[1].to_string());
+        assert_eq!("Line 3: no shared item types", reports[2].to_string());
+    }
+
+    #[test]
+    fn discovers_groups_regardless_of_line_order() {
+        let catalog = ItemCatalog::default();
+        let rucksacks = [
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "ttgJtRGJQctTZtZT",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+            "PmmdzqPrVvPwwTWBwg",
+        ];
+        let mut groups = discover_groups(&rucksacks, 3, &catalog).unwrap();
+        for group in &mut groups {
+            group.sort();
+            let members = group
+                .iter()
+                .map(|&index| rucksacks[index])
+                .collect::<Vec<_>>();
+            assert_eq!(1, catalog.common_items(&members).len());
+        }
+        groups.sort();
+        assert_eq!(vec![vec![0, 2, 4], vec![1, 3, 5]], groups);
+    }
+
+    #[test]
+    fn reports_when_no_grouping_exists() {
+        let catalog = ItemCatalog::default();
+        assert_eq!(None, discover_groups(&["ab", "ac", "de"], 3, &catalog));
+        assert_eq!(None, discover_groups(&["ab", "ab", "ab"], 3, &catalog));
+        assert_eq!(None, discover_groups(&["ab", "ab"], 3, &catalog));
+        assert_eq!(Some(vec![]), discover_groups(&[], 3, &catalog));
+    }
+
+    #[test]
+    fn accepts_valid_rucksacks() {
+        let input =
+            "vJrwpWtwJgWrhcsFMMfFFhFp\njqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL\nPmmdzqPrVvPwwTWBwg\n";
+        assert_eq!(
+            Ok(input.lines().collect()),
+            validate(input, 3, &ItemCatalog::default())
+        );
+    }
+
+    #[test]
+    fn reports_every_problem_with_its_line() {
+        let input = "ab1a\nabcab\nabcd\naa\n";
+        let errors = validate(input, 3, &ItemCatalog::default()).unwrap_err();
+        assert_eq!(
+            vec![
+                RucksackError::InvalidItem { line: 1, item: '1' },
+                RucksackError::OddLength { line: 2, length: 5 },
+                RucksackError::NoSharedItem { line: 3 },
+                RucksackError::IncompleteGroup { line: 4, size: 1 },
+            ],
+            errors
+        );
+        assert_eq!("Line 1: '1' is not a valid item", errors[0].to_string());
+    }
+
+    #[test]
+    fn reports_groups_without_badge() {
+        let input = "aa\nbb\naa\n";
+        let errors = validate(input, 3, &ItemCatalog::default()).unwrap_err();
+        assert_eq!(vec![RucksackError::NoBadge { line: 1 }], errors);
+    }
+
+    #[test]
+    #[should_panic]
+    fn refuses_priority_for_invalid_item() {
+        super::char_priority('1');
+    }
+
     #[test]
     fn calculates_points_for_char() {
         let priority = super::char_priority('p');
@@ -79,8 +762,9 @@ mod tests {
 }
 
 pub(crate) fn char_priority(c: char) -> u32 {
-    match c.is_lowercase() {
-        true => (c as u32) - 96,
-        false => (c as u32) - 38,
+    match c {
+        'a'..='z' => (c as u32) - 96,
+        'A'..='Z' => (c as u32) - 38,
+        _ => panic!("{c:?} is not a valid item"),
     }
 }
@@ -1,5 +1,12 @@
-use std::collections::HashSet;
-use std::hash::Hash;
+use std::cmp::Reverse;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::interval::Interval;
+use crate::interval_set::{covered_at_least, intervals_from_lines, IntervalSet};
 
 pub(crate) fn solve() {
     let input = std::fs::read_to_string("../input/day4.txt").unwrap();
@@ -20,81 +27,583 @@ pub(crate) fn solve() {
         .count();
 
     println!("Day 4 Part Two: {}", part_two);
+
+    let assignments = intervals_from_lines(&input).unwrap();
+    let covered = IntervalSet::from_intervals(assignments.iter().copied());
+    let uncovered = covered.complement(covered.bounds().unwrap());
+    println!("Day 4 sections no elf covers: {uncovered}");
+    let crowded = covered_at_least(&assignments, 3);
+    println!(
+        "Day 4 sections covered by three or more elves: {} ({} sections)",
+        crowded,
+        crowded.len()
+    );
+
+    let mut reports = overlap_report(&input.lines().map(parse_ranges).collect::<Vec<_>>());
+    println!("{}", OverlapStatistics::from_reports(&reports));
+    sort_reports(&mut reports, SortKey::Overlap);
+    println!("Pairs with the most overlap:");
+    for report in reports.iter().take(10) {
+        println!("  {report}");
+    }
+
+    let reassignments = input
+        .lines()
+        .map(parse_ranges)
+        .map(reassign)
+        .collect::<Vec<_>>();
+    let saved: u128 = reassignments.iter().map(Reassignment::saved).sum();
+    println!("Day 4 sections saved by reassigning pairs: {saved}");
+}
+
+/// New sections for a pair of elves that cover the same sections as before, without overlap.
+#[derive(Debug, PartialEq)]
+struct Reassignment {
+    original: (Interval, Interval),
+    /// In the order of the original pair. An elf may end up without any section if the pair only
+    /// covers a single one.
+    proposed: (Option<Interval>, Option<Interval>),
+}
+
+impl Reassignment {
+    fn saved(&self) -> u128 {
+        let before = self.original.0.len() + self.original.1.len();
+        let after = [self.proposed.0, self.proposed.1]
+            .iter()
+            .flatten()
+            .map(Interval::len)
+            .sum::<u128>();
+        before - after
+    }
+}
+
+impl Display for Reassignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let format = |interval: Option<Interval>| match interval {
+            Some(interval) => interval.to_string(),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "{},{} -> {},{}",
+            self.original.0,
+            self.original.1,
+            format(self.proposed.0),
+            format(self.proposed.1)
+        )
+    }
+}
+
+/// Splits the sections of a pair in half, the elf starting further left takes the left half.
+/// Pairs with a gap between their sections already don't overlap and can't be balanced without
+/// assigning one elf sections on both sides of the gap, so they keep their sections.
+fn reassign(pair: (Interval, Interval)) -> Reassignment {
+    let union = IntervalSet::from_intervals([pair.0, pair.1]);
+    let proposed = match union.intervals() {
+        [covered] => {
+            let left = covered.len() - covered.len() / 2;
+            let (left, right) = if left == covered.len() {
+                (Some(*covered), None)
+            } else {
+                let middle = covered.start + left as i64;
+                (
+                    Some(Interval::new(covered.start, middle - 1)),
+                    Some(Interval::new(middle, covered.end)),
+                )
+            };
+            if pair.0.start <= pair.1.start {
+                (left, right)
+            } else {
+                (right, left)
+            }
+        }
+        _ => (Some(pair.0), Some(pair.1)),
+    };
+    Reassignment {
+        original: pair,
+        proposed,
+    }
+}
+
+/// How the sections of two elves relate to each other.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+enum Relationship {
+    Disjoint,
+    Adjacent,
+    Overlapping,
+    Contained,
+    Equal,
+}
+
+impl Relationship {
+    fn of(first: &Interval, second: &Interval) -> Self {
+        if first == second {
+            Relationship::Equal
+        } else if first.contains(second) || second.contains(first) {
+            Relationship::Contained
+        } else if first.overlaps(second) {
+            Relationship::Overlapping
+        } else if first.is_adjacent_to(second) {
+            Relationship::Adjacent
+        } else {
+            Relationship::Disjoint
+        }
+    }
+}
+
+impl Display for Relationship {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Relationship::Disjoint => "disjoint",
+            Relationship::Adjacent => "adjacent",
+            Relationship::Overlapping => "overlapping",
+            Relationship::Contained => "contained",
+            Relationship::Equal => "equal",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PairReport {
+    line: usize,
+    first: Interval,
+    second: Interval,
+    relationship: Relationship,
+    overlap: u128,
+}
+
+impl PairReport {
+    /// Share of each elf's sections that the other elf cleans as well.
+    fn duplicated(&self) -> (f64, f64) {
+        let overlap = self.overlap as f64;
+        (
+            overlap / self.first.len() as f64,
+            overlap / self.second.len() as f64,
+        )
+    }
+}
+
+impl Display for PairReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (first, second) = self.duplicated();
+        write!(
+            f,
+            "line {:>4}: {:>13} {:<11} overlap {:>3}, duplicated {:>5.1}% / {:>5.1}%",
+            self.line,
+            format!("{},{}", self.first, self.second),
+            self.relationship,
+            self.overlap,
+            first * 100.0,
+            second * 100.0
+        )
+    }
+}
+
+fn overlap_report(pairs: &[(Interval, Interval)]) -> Vec<PairReport> {
+    pairs
+        .iter()
+        .enumerate()
+        .map(|(index, &(first, second))| PairReport {
+            line: index + 1,
+            first,
+            second,
+            relationship: Relationship::of(&first, &second),
+            overlap: first.intersection(&second).map_or(0, |i| i.len()),
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum SortKey {
+    Line,
+    Relationship,
+    /// Most overlap first.
+    Overlap,
+    /// Highest share of duplicated work of either elf first.
+    Duplication,
+}
+
+fn sort_reports(reports: &mut [PairReport], key: SortKey) {
+    match key {
+        SortKey::Line => reports.sort_by_key(|report| report.line),
+        SortKey::Relationship => reports.sort_by_key(|report| (report.relationship, report.line)),
+        SortKey::Overlap => reports.sort_by_key(|report| (Reverse(report.overlap), report.line)),
+        SortKey::Duplication => reports.sort_by(|a, b| {
+            let (a_first, a_second) = a.duplicated();
+            let (b_first, b_second) = b.duplicated();
+            b_first
+                .max(b_second)
+                .total_cmp(&a_first.max(a_second))
+                .then(a.line.cmp(&b.line))
+        }),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct OverlapStatistics {
+    pairs: usize,
+    relationships: Vec<(Relationship, usize)>,
+    total_overlap: u128,
+    total_sections: u128,
+    /// Average share of an elf's sections that its partner cleans as well.
+    mean_duplicated: f64,
+}
+
+impl OverlapStatistics {
+    fn from_reports(reports: &[PairReport]) -> Self {
+        let relationships = reports
+            .iter()
+            .map(|report| report.relationship)
+            .counts()
+            .into_iter()
+            .sorted()
+            .collect();
+        let total_sections = reports
+            .iter()
+            .map(|report| report.first.len() + report.second.len())
+            .sum();
+        let duplicated_sum: f64 = reports
+            .iter()
+            .map(|report| report.duplicated().0 + report.duplicated().1)
+            .sum();
+        OverlapStatistics {
+            pairs: reports.len(),
+            relationships,
+            total_overlap: reports.iter().map(|report| report.overlap).sum(),
+            total_sections,
+            mean_duplicated: if reports.is_empty() {
+                0.0
+            } else {
+                duplicated_sum / (2 * reports.len()) as f64
+            },
+        }
+    }
+}
+
+impl Display for OverlapStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} pairs", self.pairs)?;
+        for (relationship, count) in &self.relationships {
+            writeln!(f, "  {relationship:<11} {count}")?;
+        }
+        writeln!(
+            f,
+            "{} of {} assigned sections are cleaned twice",
+            self.total_overlap, self.total_sections
+        )?;
+        write!(
+            f,
+            "On average {:.1}% of an elf's work is duplicated",
+            self.mean_duplicated * 100.0
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day4::one_tuple_component_completely_contains_the_other;
     use crate::day4::parse_ranges;
+    use crate::day4::parse_zones;
     use crate::day4::tuple_components_overlap;
-    use std::collections::HashSet;
+    use crate::day4::{
+        overlap_report, reassign, sort_reports, OverlapStatistics, PairReport, Relationship,
+        SortKey, Zone,
+    };
+    use crate::interval::Interval;
 
     #[test]
     fn splits_str_into_tuple() {
         let input = "2-4,6-8";
-        let sections: (HashSet<i32>, HashSet<i32>) = parse_ranges(input);
+        let sections: (Interval, Interval) = parse_ranges(input);
+        assert_eq!((Interval::new(2, 4), Interval::new(6, 8)), sections)
+    }
+
+    #[test]
+    fn supports_64_bit_section_ids() {
+        let input = "1-1000000000,999999999-10000000000";
+        let sections = parse_ranges(input);
         assert_eq!(
-            (HashSet::from([2, 3, 4]), HashSet::from([6, 7, 8])),
+            (
+                Interval::new(1, 1_000_000_000),
+                Interval::new(999_999_999, 10_000_000_000)
+            ),
             sections
-        )
+        );
+        assert!(tuple_components_overlap(sections));
+        assert!(!one_tuple_component_completely_contains_the_other(sections));
     }
 
     #[test]
     fn determine_if_one_tuple_component_completely_contains_the_other() {
-        let given = (HashSet::from([2, 3, 4]), HashSet::from([6, 7, 8]));
+        let given = (Interval::new(2, 4), Interval::new(6, 8));
         let actual = one_tuple_component_completely_contains_the_other(given);
         assert!(!actual);
 
-        let given = (HashSet::from([2, 3, 4]), HashSet::from([3]));
+        let given = (Interval::new(2, 4), Interval::new(3, 3));
         let actual = one_tuple_component_completely_contains_the_other(given);
         assert!(actual);
     }
 
+    #[test]
+    fn determines_relationship_of_pair() {
+        let pairs = [
+            ("2-4,6-8", Relationship::Disjoint, 0),
+            ("2-4,5-8", Relationship::Adjacent, 0),
+            ("5-7,7-9", Relationship::Overlapping, 1),
+            ("2-8,3-7", Relationship::Contained, 5),
+            ("6-6,4-6", Relationship::Contained, 1),
+            ("3-5,3-5", Relationship::Equal, 3),
+        ];
+        let reports = overlap_report(&pairs.map(|(line, _, _)| parse_ranges(line)));
+        for (report, (_, relationship, overlap)) in reports.iter().zip(pairs) {
+            assert_eq!(relationship, report.relationship);
+            assert_eq!(overlap, report.overlap);
+        }
+        assert_eq!((1.0, 1.0 / 3.0), reports[4].duplicated());
+    }
+
+    #[test]
+    fn sorts_reports_and_aggregates_statistics() {
+        let pairs = ["2-4,6-8", "2-8,3-7", "5-7,7-9", "6-6,4-6"].map(parse_ranges);
+        let mut reports = overlap_report(&pairs);
+
+        let statistics = OverlapStatistics::from_reports(&reports);
+        assert_eq!(4, statistics.pairs);
+        assert_eq!(7, statistics.total_overlap);
+        assert_eq!(
+            vec![
+                (Relationship::Disjoint, 1),
+                (Relationship::Overlapping, 1),
+                (Relationship::Contained, 2)
+            ],
+            statistics.relationships
+        );
+
+        let lines = |reports: &[PairReport]| reports.iter().map(|r| r.line).collect::<Vec<_>>();
+        sort_reports(&mut reports, SortKey::Overlap);
+        assert_eq!(vec![2, 3, 4, 1], lines(&reports));
+        sort_reports(&mut reports, SortKey::Duplication);
+        assert_eq!(vec![2, 4, 3, 1], lines(&reports));
+        sort_reports(&mut reports, SortKey::Relationship);
+        assert_eq!(vec![1, 3, 2, 4], lines(&reports));
+        sort_reports(&mut reports, SortKey::Line);
+        assert_eq!(vec![1, 2, 3, 4], lines(&reports));
+    }
+
+    #[test]
+    fn reassigns_pairs_without_overlap() {
+        let reassignment = reassign(parse_ranges("2-6,4-8"));
+        let expected = (Some(Interval::new(2, 5)), Some(Interval::new(6, 8)));
+        assert_eq!(expected, reassignment.proposed);
+        assert_eq!(3, reassignment.saved());
+
+        let reassignment = reassign(parse_ranges("3-7,2-8"));
+        let expected = (Some(Interval::new(6, 8)), Some(Interval::new(2, 5)));
+        assert_eq!(expected, reassignment.proposed);
+        assert_eq!(5, reassignment.saved());
+
+        let reassignment = reassign(parse_ranges("2-3,4-9"));
+        let expected = (Some(Interval::new(2, 5)), Some(Interval::new(6, 9)));
+        assert_eq!(expected, reassignment.proposed);
+        assert_eq!(0, reassignment.saved());
+    }
+
+    #[test]
+    fn keeps_pairs_with_gap_and_handles_single_section() {
+        let reassignment = reassign(parse_ranges("2-4,6-8"));
+        let expected = (Some(Interval::new(2, 4)), Some(Interval::new(6, 8)));
+        assert_eq!(expected, reassignment.proposed);
+        assert_eq!(0, reassignment.saved());
+
+        let reassignment = reassign(parse_ranges("5-5,5-5"));
+        assert_eq!((Some(Interval::new(5, 5)), None), reassignment.proposed);
+        assert_eq!(1, reassignment.saved());
+        assert_eq!("5-5,5-5 -> 5-5,-", reassignment.to_string());
+    }
+
+    #[test]
+    fn parses_zones_of_any_dimension() {
+        let (first, second) = parse_zones("2-4x1-3,3-5x2-2");
+        assert_eq!(2, first.dimensions());
+        assert_eq!(Some(9), first.volume());
+
+        let full = Interval::new(i64::MIN, i64::MAX);
+        let line = Zone { axes: vec![full] };
+        assert_eq!(Some(1 << 64), line.volume());
+        let plane = Zone {
+            axes: vec![full, full],
+        };
+        assert_eq!(None, plane.volume());
+        assert_eq!("3-5x2-2", second.to_string());
+
+        let (first, second) = parse_zones("2-4,6-8");
+        assert_eq!((first.axes[0], second.axes[0]), parse_ranges("2-4,6-8"));
+    }
+
+    #[test]
+    fn determines_containment_and_overlap_of_zones() {
+        let zones = parse_zones("1-5x1-5x1-5,2-3x2-3x5-5");
+        assert!(one_tuple_component_completely_contains_the_other(
+            zones.clone()
+        ));
+        assert!(tuple_components_overlap(zones));
+
+        let zones = parse_zones("2-4x1-3,3-5x2-2");
+        assert!(!one_tuple_component_completely_contains_the_other(
+            zones.clone()
+        ));
+        assert!(tuple_components_overlap(zones.clone()));
+        assert_eq!(Some(2), zones.0.intersection_volume(&zones.1));
+
+        // Overlapping on one axis isn't enough.
+        let zones = parse_zones("2-4x1-3,3-5x4-6");
+        assert!(!tuple_components_overlap(zones.clone()));
+        assert_eq!(Some(0), zones.0.intersection_volume(&zones.1));
+    }
+
+    #[test]
+    fn keeps_behaviour_for_one_dimensional_zones() {
+        for line in [
+            "2-4,6-8", "2-3,4-5", "5-7,7-9", "2-8,3-7", "6-6,4-6", "2-6,4-8",
+        ] {
+            let ranges = parse_ranges(line);
+            let zones = parse_zones(line);
+            assert_eq!(
+                one_tuple_component_completely_contains_the_other(ranges),
+                one_tuple_component_completely_contains_the_other(zones.clone())
+            );
+            assert_eq!(
+                tuple_components_overlap(ranges),
+                tuple_components_overlap(zones)
+            );
+        }
+    }
+
     #[test]
     fn determine_if_tuple_components_overlap() {
-        let given = (HashSet::from([2, 3, 4]), HashSet::from([6, 7, 8]));
+        let given = (Interval::new(2, 4), Interval::new(6, 8));
         let actual = tuple_components_overlap(given);
         assert!(!actual);
 
-        let given = (HashSet::from([2, 3, 4]), HashSet::from([4, 5]));
+        let given = (Interval::new(2, 4), Interval::new(4, 5));
         let actual = tuple_components_overlap(given);
         assert!(actual);
     }
 }
 
-fn one_tuple_component_completely_contains_the_other<T>(tuple: (HashSet<T>, HashSet<T>)) -> bool
-where
-    T: Eq + Hash,
-{
-    let first = tuple.0;
-    let second = tuple.1;
+fn one_tuple_component_completely_contains_the_other<R: Region>(tuple: (R, R)) -> bool {
+    let (first, second) = tuple;
+    first.contains(&second) || second.contains(&first)
+}
 
-    first.is_superset(&second) || first.is_subset(&second)
+fn tuple_components_overlap<R: Region>(tuple: (R, R)) -> bool {
+    let (first, second) = tuple;
+    first.overlaps(&second)
 }
 
-fn tuple_components_overlap<T>(tuple: (HashSet<T>, HashSet<T>)) -> bool
-where
-    T: Eq + Hash,
-{
-    let first = tuple.0;
-    let second = tuple.1;
-    !first.is_disjoint(&second)
+fn parse_ranges(line: &str) -> (Interval, Interval) {
+    let (part_one, part_two) = line.split_once(',').unwrap();
+    let range_one = Interval::from_str(part_one).unwrap();
+    let range_two = Interval::from_str(part_two).unwrap();
+    (range_one, range_two)
 }
 
-fn parse_ranges(line: &str) -> (HashSet<i32>, HashSet<i32>) {
+/// Same as `parse_ranges`, but for zones like `2-4x1-3,3-5x2-2`.
+fn parse_zones(line: &str) -> (Zone, Zone) {
     let (part_one, part_two) = line.split_once(',').unwrap();
-    let set_one = set_for_single_range(part_one);
-    let set_two = set_for_single_range(part_two);
-    (set_one, set_two)
+    let zone_one = Zone::from_str(part_one).unwrap();
+    let zone_two = Zone::from_str(part_two).unwrap();
+    assert_eq!(
+        zone_one.dimensions(),
+        zone_two.dimensions(),
+        "Zones of a pair must have the same number of dimensions"
+    );
+    (zone_one, zone_two)
+}
+
+/// Sections of any dimension that one elf may be assigned.
+trait Region {
+    fn contains(&self, other: &Self) -> bool;
+    fn overlaps(&self, other: &Self) -> bool;
+}
+
+impl Region for Interval {
+    fn contains(&self, other: &Self) -> bool {
+        Interval::contains(self, other)
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        Interval::overlaps(self, other)
+    }
+}
+
+/// An axis-aligned box of sections, with one interval per dimension.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Zone {
+    axes: Vec<Interval>,
+}
+
+impl Zone {
+    fn dimensions(&self) -> usize {
+        self.axes.len()
+    }
+
+    /// Number of sections in the zone, `None` if that doesn't fit in a `u128`.
+    fn volume(&self) -> Option<u128> {
+        self.axes
+            .iter()
+            .try_fold(1u128, |volume, axis| volume.checked_mul(axis.len()))
+    }
+
+    fn intersection(&self, other: &Zone) -> Option<Zone> {
+        let axes = self
+            .axes
+            .iter()
+            .zip(&other.axes)
+            .map(|(a, b)| a.intersection(b))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Zone { axes })
+    }
+
+    fn intersection_volume(&self, other: &Zone) -> Option<u128> {
+        self.intersection(other)
+            .map_or(Some(0), |zone| zone.volume())
+    }
+}
+
+impl Region for Zone {
+    fn contains(&self, other: &Self) -> bool {
+        assert_eq!(self.dimensions(), other.dimensions());
+        self.axes
+            .iter()
+            .zip(&other.axes)
+            .all(|(a, b)| a.contains(b))
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        assert_eq!(self.dimensions(), other.dimensions());
+        self.axes
+            .iter()
+            .zip(&other.axes)
+            .all(|(a, b)| a.overlaps(b))
+    }
+}
+
+impl FromStr for Zone {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let axes = s
+            .split('x')
+            .map(Interval::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Zone { axes })
+    }
 }
 
-fn set_for_single_range(string: &str) -> HashSet<i32> {
-    let values = string.split_once('-').unwrap();
-    let start: i32 = values.0.parse().unwrap();
-    let end: i32 = values.1.parse().unwrap();
-    let mut set = HashSet::new();
-    for i in start..=end {
-        set.insert(i);
+impl Display for Zone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.axes.iter().join("x"))
     }
-    set
 }
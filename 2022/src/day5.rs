@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 
@@ -9,43 +10,105 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, take},
     combinator::{all_consuming, map, opt},
-    sequence::{delimited, preceded},
+    multi::many0,
+    sequence::{delimited, preceded, terminated},
     Finish, IResult,
 };
 
 pub(crate) fn solve() {
     let input = std::fs::read_to_string("../input/day5.txt").unwrap();
-    let mut lines = input.lines();
+    let (mut piles, instructions) = parse_input(&input);
+    println!("{piles:?}");
 
-    let crate_lines: Vec<_> = (&mut lines)
-        .map_while(|line| {
-            all_consuming(parse_crate_line)(line)
-                .finish()
-                .ok()
-                .map(|(_, line)| line)
-        })
-        .collect();
+    let optimization = optimize(&instructions, CraneModel::CrateMover9001);
+    print!("{optimization}");
+    assert!(optimization.is_equivalent(&piles, &instructions, CraneModel::CrateMover9001));
 
-    let mut piles = Piles(transpose_rev(crate_lines));
-    println!("{piles:?}");
+    for instruction in instructions {
+        println!("{instruction:?}");
+        piles.execute2(instruction);
+        print!("{piles:?}");
+    }
+    piles.print_top_elements();
+}
 
-    assert!(lines.next().unwrap().is_empty());
+/// Crate drawings as in the puzzle input, or one `N: A B C` line per pile listing its crates from
+/// bottom to top. Both are followed by an empty line and the instructions.
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum InputFormat {
+    Drawing,
+    Structured,
+}
+
+fn parse_input(input: &str) -> (Piles, Vec<Instruction>) {
+    let format = match input.lines().next() {
+        Some(line) if all_consuming(parse_pile_line)(line).is_ok() => InputFormat::Structured,
+        _ => InputFormat::Drawing,
+    };
+    let mut lines = input.lines();
+
+    let piles = match format {
+        InputFormat::Drawing => {
+            let crate_lines: Vec<_> = (&mut lines)
+                .map_while(|line| {
+                    all_consuming(parse_crate_line)(line)
+                        .finish()
+                        .ok()
+                        .map(|(_, line)| line)
+                })
+                .collect();
+            assert!(lines.next().unwrap().is_empty());
+            Piles(transpose_rev(crate_lines))
+        }
+        InputFormat::Structured => {
+            let piles = (&mut lines)
+                .take_while(|line| !line.is_empty())
+                .enumerate()
+                .map(|(index, line)| {
+                    let (number, pile) = all_consuming(parse_pile_line)(line).finish().unwrap().1;
+                    assert_eq!(number, index, "piles must be listed in order");
+                    pile
+                })
+                .collect();
+            Piles(piles)
+        }
+    };
 
     let instructions = lines
         .map(|line| all_consuming(parse_instruction)(line).finish().unwrap().1)
         .collect::<Vec<_>>();
 
+    (piles, instructions)
+}
+
+/// Writes piles and instructions in the given format, so that `parse_input` reads them back.
+fn format_input(piles: &Piles, instructions: &[Instruction], format: InputFormat) -> String {
+    let mut output = match format {
+        InputFormat::Drawing => piles.drawing(),
+        InputFormat::Structured => piles
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, pile)| format!("{}: {}\n", index + 1, pile.iter().join(" ")))
+            .collect(),
+    };
+    output.push('\n');
     for instruction in instructions {
-        println!("{instruction:?}");
-        piles.execute2(instruction);
-        print!("{piles:?}");
+        output.push_str(&format!("{instruction}\n"));
     }
-    piles.print_top_elements();
+    output
+}
+
+/// Converts an input from one format to the other.
+fn convert(input: &str, format: InputFormat) -> String {
+    let (piles, instructions) = parse_input(input);
+    format_input(&piles, &instructions, format)
 }
 
 #[derive(PartialEq, Copy, Clone)]
 struct Crate(char);
 
+#[derive(PartialEq, Clone)]
 struct Piles(Vec<Vec<Crate>>);
 
 impl Debug for Piles {
@@ -66,6 +129,9 @@ impl Piles {
     }
 
     fn execute2(&mut self, ins: Instruction) {
+        if ins.from == ins.to {
+            return;
+        }
         let [src, dst] = self
             .0
             .get_many_mut([ins.from, ins.to])
@@ -74,6 +140,36 @@ impl Piles {
         dst.extend(src.drain((src.len() - ins.amount)..))
     }
 
+    fn run(&mut self, instructions: &[Instruction], model: CraneModel) {
+        for &instruction in instructions {
+            match model {
+                CraneModel::CrateMover9000 => self.execute(instruction),
+                CraneModel::CrateMover9001 => self.execute2(instruction),
+            }
+        }
+    }
+
+    fn drawing(&self) -> String {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        let mut drawing = String::new();
+        for level in (0..height).rev() {
+            let line = self
+                .0
+                .iter()
+                .map(|pile| match pile.get(level) {
+                    Some(krate) => format!("[{krate}]"),
+                    None => "   ".to_string(),
+                })
+                .join(" ");
+            drawing.push_str(&line);
+            drawing.push('\n');
+        }
+        let numbers = (1..=self.0.len()).map(|n| format!(" {n} ")).join(" ");
+        drawing.push_str(&numbers);
+        drawing.push('\n');
+        drawing
+    }
+
     fn print_top_elements(&self) {
         println!(
             "top elements = {}",
@@ -82,23 +178,213 @@ impl Piles {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Copy, Clone)]
 struct Instruction {
     from: usize,
     to: usize,
     amount: usize,
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.amount,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+/// The CrateMover 9000 moves crates one at a time, the 9001 moves them all at once.
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum CraneModel {
+    CrateMover9000,
+    CrateMover9001,
+}
+
+#[derive(Debug, PartialEq)]
+enum Rewrite {
+    Dropped(Instruction),
+    Cancelled(Instruction, Instruction),
+    Merged(Instruction, Instruction, Instruction),
+}
+
+impl Display for Rewrite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Rewrite::Dropped(ins) => write!(f, "removed no-op '{ins}'"),
+            Rewrite::Cancelled(first, second) => {
+                write!(f, "removed '{first}' and '{second}', they cancel out")
+            }
+            Rewrite::Merged(first, second, merged) => {
+                write!(f, "merged '{first}' and '{second}' into '{merged}'")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Optimization {
+    program: Vec<Instruction>,
+    rewrites: Vec<Rewrite>,
+    original_len: usize,
+}
+
+impl Optimization {
+    /// Runs the original and the optimized program on copies of `piles` and compares the results.
+    fn is_equivalent(&self, piles: &Piles, original: &[Instruction], model: CraneModel) -> bool {
+        let mut expected = piles.clone();
+        expected.run(original, model);
+        let mut actual = piles.clone();
+        actual.run(&self.program, model);
+        expected == actual
+    }
+}
+
+impl Display for Optimization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Optimized {} instructions down to {}",
+            self.original_len,
+            self.program.len()
+        )?;
+        for rewrite in &self.rewrites {
+            writeln!(f, "  {rewrite}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites `instructions` into a shorter program that leaves the piles in the same state.
+///
+/// Every instruction is pushed onto the output and then combined with its predecessor for as
+/// long as one of the rules for the given crane model applies, so rewrites can cascade.
+fn optimize(instructions: &[Instruction], model: CraneModel) -> Optimization {
+    let mut program: Vec<Instruction> = vec![];
+    let mut rewrites = vec![];
+
+    for &instruction in instructions {
+        if instruction.amount == 0 || instruction.from == instruction.to {
+            rewrites.push(Rewrite::Dropped(instruction));
+            continue;
+        }
+        let mut current = instruction;
+        loop {
+            let Some(&previous) = program.last() else {
+                program.push(current);
+                break;
+            };
+            match combine(previous, current, model) {
+                Some(Combined::Cancelled) => {
+                    program.pop();
+                    rewrites.push(Rewrite::Cancelled(previous, current));
+                    break;
+                }
+                Some(Combined::Merged(merged)) => {
+                    program.pop();
+                    rewrites.push(Rewrite::Merged(previous, current, merged));
+                    current = merged;
+                }
+                None => {
+                    program.push(current);
+                    break;
+                }
+            }
+        }
+    }
+
+    Optimization {
+        program,
+        rewrites,
+        original_len: instructions.len(),
+    }
+}
+
+enum Combined {
+    Cancelled,
+    Merged(Instruction),
+}
+
+/// Combines two consecutive instructions, if the crane model allows for it.
+fn combine(first: Instruction, second: Instruction, model: CraneModel) -> Option<Combined> {
+    let same_direction = first.from == second.from && first.to == second.to;
+    let back_and_forth = first.from == second.to && first.to == second.from;
+
+    match model {
+        // Moving crates one at a time reverses them, so moving them back restores the order.
+        CraneModel::CrateMover9000 if same_direction => Some(Combined::Merged(Instruction {
+            amount: first.amount + second.amount,
+            ..first
+        })),
+        CraneModel::CrateMover9000 if back_and_forth => {
+            Some(match first.amount.cmp(&second.amount) {
+                Ordering::Equal => Combined::Cancelled,
+                Ordering::Greater => Combined::Merged(Instruction {
+                    amount: first.amount - second.amount,
+                    ..first
+                }),
+                Ordering::Less => Combined::Merged(Instruction {
+                    amount: second.amount - first.amount,
+                    ..second
+                }),
+            })
+        }
+        // Moving crates all at once keeps them together, so only whole stacks can be tracked. A
+        // single crate is moved the same way by both models.
+        _ if first.amount == second.amount
+            && first.to == second.from
+            && (model == CraneModel::CrateMover9001 || first.amount == 1) =>
+        {
+            if back_and_forth {
+                Some(Combined::Cancelled)
+            } else {
+                Some(Combined::Merged(Instruction {
+                    from: first.from,
+                    to: second.to,
+                    amount: first.amount,
+                }))
+            }
+        }
+        _ => None,
+    }
+}
+
 // Not actually my own solution. This one was a touch too tricky to figure out on my own, in a
 // language / ecosystem I am not (yet) familiar with. Props go to @fasterthanlime.
 // https://fasterthanli.me/series/advent-of-code-2022/part-5
 #[cfg(test)]
 mod tests {
     use crate::day5::{
-        parse_crate, parse_crate_line, parse_crate_or_hole, parse_digit, parse_hole,
-        parse_instruction, parse_pile_number, Crate, Instruction,
+        convert, optimize, parse_crate, parse_crate_line, parse_crate_or_hole, parse_digit,
+        parse_hole, parse_input, parse_instruction, parse_pile_line, parse_pile_number, CraneModel,
+        Crate, InputFormat, Instruction, Piles, Rewrite,
     };
 
+    const DRAWING: &str = concat!(
+        "    [D]    \n",
+        "[N] [C]    \n",
+        "[Z] [M] [P]\n",
+        " 1   2   3 \n",
+        "\n",
+        "move 1 from 2 to 1\n",
+        "move 3 from 1 to 3\n",
+        "move 2 from 2 to 1\n",
+        "move 1 from 1 to 2\n",
+    );
+
+    const STRUCTURED: &str = "1: Z N
+2: M C D
+3: P
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
     #[test]
     fn parses_crate() {
         let a = "[A]";
@@ -175,6 +461,102 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn parses_pile_line() {
+        let result = parse_pile_line("2: M C D").unwrap().1;
+        assert_eq!(result, (1, vec![Crate('M'), Crate('C'), Crate('D')]));
+
+        let result = parse_pile_line("3:").unwrap().1;
+        assert_eq!(result, (2, vec![]));
+    }
+
+    #[test]
+    fn parses_both_input_formats_into_the_same_piles() {
+        let (piles, instructions) = parse_input(DRAWING);
+        assert_eq!(piles, self::piles());
+        assert_eq!(instructions[0], instruction(1, 2, 1));
+        assert_eq!(instructions.len(), 4);
+
+        let (structured_piles, structured_instructions) = parse_input(STRUCTURED);
+        assert_eq!(structured_piles, piles);
+        assert_eq!(structured_instructions, instructions);
+    }
+
+    #[test]
+    fn converts_between_input_formats() {
+        assert_eq!(convert(DRAWING, InputFormat::Structured), STRUCTURED);
+        assert_eq!(convert(STRUCTURED, InputFormat::Drawing), DRAWING);
+    }
+
+    fn instruction(amount: usize, from: usize, to: usize) -> Instruction {
+        Instruction {
+            amount,
+            from: from - 1,
+            to: to - 1,
+        }
+    }
+
+    fn piles() -> Piles {
+        Piles(vec![
+            vec![Crate('Z'), Crate('N')],
+            vec![Crate('M'), Crate('C'), Crate('D')],
+            vec![Crate('P')],
+        ])
+    }
+
+    #[test]
+    fn optimizer_drops_no_op_instructions() {
+        let program = vec![instruction(0, 1, 2), instruction(2, 3, 3)];
+        let optimization = optimize(&program, CraneModel::CrateMover9000);
+        assert!(optimization.program.is_empty());
+        assert_eq!(
+            optimization.rewrites,
+            vec![Rewrite::Dropped(program[0]), Rewrite::Dropped(program[1])]
+        );
+    }
+
+    #[test]
+    fn optimizer_cancels_moves_back_and_forth() {
+        let program = vec![
+            instruction(1, 1, 2),
+            instruction(2, 2, 3),
+            instruction(2, 3, 2),
+            instruction(1, 2, 1),
+        ];
+        for model in [CraneModel::CrateMover9000, CraneModel::CrateMover9001] {
+            let optimization = optimize(&program, model);
+            assert!(optimization.program.is_empty());
+            assert!(optimization.is_equivalent(&piles(), &program, model));
+        }
+    }
+
+    #[test]
+    fn optimizer_merges_moves_one_crate_at_a_time() {
+        let program = vec![
+            instruction(1, 2, 1),
+            instruction(1, 2, 1),
+            instruction(3, 1, 2),
+        ];
+        let optimization = optimize(&program, CraneModel::CrateMover9000);
+        assert_eq!(optimization.program, vec![instruction(1, 1, 2)]);
+        assert!(optimization.is_equivalent(&piles(), &program, CraneModel::CrateMover9000));
+
+        // Moving the same crates all at once stacks them differently, so nothing can be merged.
+        let optimization = optimize(&program, CraneModel::CrateMover9001);
+        assert_eq!(optimization.program, program);
+    }
+
+    #[test]
+    fn optimizer_merges_chained_moves_all_at_once() {
+        let program = vec![instruction(2, 2, 1), instruction(2, 1, 3)];
+        let optimization = optimize(&program, CraneModel::CrateMover9001);
+        assert_eq!(optimization.program, vec![instruction(2, 2, 3)]);
+        assert!(optimization.is_equivalent(&piles(), &program, CraneModel::CrateMover9001));
+
+        let optimization = optimize(&program, CraneModel::CrateMover9000);
+        assert_eq!(optimization.program, program);
+    }
 }
 
 fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
@@ -188,6 +570,14 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     )(input)
 }
 
+fn parse_pile_line(input: &str) -> IResult<&str, (usize, Vec<Crate>)> {
+    let crates = many0(preceded(
+        tag(" "),
+        map(take(1_usize), |s: &str| Crate(s.chars().next().unwrap())),
+    ));
+    tuple((terminated(parse_pile_number, tag(":")), crates))(input)
+}
+
 fn parse_pile_number(input: &str) -> IResult<&str, usize> {
     map(parse_digit, |i| i - 1)(input)
 }
@@ -1,73 +1,46 @@
-use std::collections::{HashSet, VecDeque};
+use std::cmp::Reverse;
+use std::fs::File;
 use std::hash::Hash;
+use std::io;
+use std::io::{ErrorKind, Read};
+use std::iter::Enumerate;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use itertools::Itertools;
+
+use crate::ring_buffer::CountingRingBuffer;
 
 #[cfg(test)]
 mod tests {
     use std::char;
-    use std::collections::VecDeque;
-
-    use crate::day6::Buffer;
+    use std::io::Read;
 
-    #[test]
-    fn can_add_elements_to_buffer() {
-        let mut buffer: Buffer<usize> = Buffer::with_limit(5);
-        buffer.add(1);
-        assert_eq!(buffer.deque, VecDeque::from([1]));
-        assert_eq!(1, buffer.len());
-    }
-
-    #[test]
-    fn buffer_can_contain_capacity_elements() {
-        let mut buffer = Buffer::from(vec!['a', 'b', 'c'], 4);
-        buffer.add('d');
-        assert_eq!(4, buffer.len());
-        buffer.add('e');
-        assert_eq!(4, buffer.len());
-
-        let mut buffer = Buffer::from(vec!['a'], 2);
-        buffer.add('d');
-        assert_eq!(2, buffer.len());
-        buffer.add('e');
-        assert_eq!(2, buffer.len());
-    }
-
-    #[test]
-    fn can_determine_if_all_contained_items_are_distinct() {
-        let buffer = Buffer::from(vec![1, 1], 4);
-        assert!(!buffer.are_items_distinct());
-
-        let buffer = Buffer::from(vec![1, 2], 4);
-        assert!(buffer.are_items_distinct());
-    }
-
-    #[test]
-    fn can_determine_if_buffer_it_at_capacity() {
-        let buffer = Buffer::from(vec![1, 2, 3], 4);
-        assert!(!buffer.is_at_capacity());
-
-        let buffer = Buffer::from(vec![1, 2, 3, 4], 4);
-        assert!(buffer.is_at_capacity());
-    }
+    use crate::day6::{
+        chunk_ranges, index_of_marker_in_bytes, index_of_marker_in_reader, markers_in_reader,
+        par_index_of_market, scan_reader, PARALLEL_THRESHOLD,
+    };
+    use crate::ring_buffer::CountingRingBuffer;
 
     #[test]
     fn determines_start_of_packet() {
         let input = "bvwbjplbgvbhsrlpgdmjqwftvncz";
-        let mut buffer = Buffer::with_limit(4);
+        let mut buffer = CountingRingBuffer::with_limit(4);
         let index = buffer.index_of_market(input);
         assert_eq!(5, index.unwrap());
 
         let input = "nppdvjthqldpwncqszvftbrmjlhg";
-        let mut buffer: Buffer<char> = Buffer::with_limit(4);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(4);
         let index = buffer.index_of_market(input);
         assert_eq!(6, index.unwrap());
 
         let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
-        let mut buffer: Buffer<char> = Buffer::with_limit(4);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(4);
         let index = buffer.index_of_market(input);
         assert_eq!(10, index.unwrap());
 
         let input = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
-        let mut buffer: Buffer<char> = Buffer::with_limit(4);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(4);
         let index = buffer.index_of_market(input);
         assert_eq!(11, index.unwrap());
     }
@@ -75,36 +48,218 @@ mod tests {
     #[test]
     fn determines_start_of_message() {
         let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        let mut buffer: Buffer<char> = Buffer::with_limit(14);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(14);
         let index = buffer.index_of_market(input);
         assert_eq!(19, index.unwrap());
 
         let input = "bvwbjplbgvbhsrlpgdmjqwftvncz";
-        let mut buffer: Buffer<char> = Buffer::with_limit(14);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(14);
         let index = buffer.index_of_market(input);
         assert_eq!(23, index.unwrap());
 
         let input = "nppdvjthqldpwncqszvftbrmjlhg";
-        let mut buffer: Buffer<char> = Buffer::with_limit(14);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(14);
         let index = buffer.index_of_market(input);
         assert_eq!(23, index.unwrap());
 
         let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
-        let mut buffer: Buffer<char> = Buffer::with_limit(14);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(14);
         let index = buffer.index_of_market(input);
         assert_eq!(29, index.unwrap());
 
         let input = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
-        let mut buffer: Buffer<char> = Buffer::with_limit(14);
+        let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(14);
         let index = buffer.index_of_market(input);
         assert_eq!(26, index.unwrap());
     }
 
-    impl<T> Buffer<T> {
-        fn from(vec: Vec<T>, capacity: usize) -> Self {
-            Buffer {
-                deque: VecDeque::from(vec),
-                capacity,
+    #[test]
+    fn determines_marker_for_any_item_type() {
+        let mut buffer = CountingRingBuffer::with_limit(3);
+        let index = buffer.index_of_marker([7, 7, 3, 7, 3, 1, 9]);
+        assert_eq!(Some(6), index);
+
+        let tokens = ["GET", "GET", "PUT", "POST"];
+        let mut buffer = CountingRingBuffer::with_limit(3);
+        assert_eq!(Some(4), buffer.index_of_marker(tokens));
+
+        let mut buffer = CountingRingBuffer::with_limit(4);
+        assert_eq!(None, buffer.index_of_marker([1, 2, 3]));
+    }
+
+    #[test]
+    fn determines_marker_in_bytes() {
+        let inputs = [
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "bvwbjplbgvbhsrlpgdmjqwftvncz",
+            "nppdvjthqldpwncqszvftbrmjlhg",
+            "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+            "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+        ];
+        for input in inputs {
+            for window in [4, 14] {
+                let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(window);
+                let expected = buffer.index_of_market(input);
+                assert_eq!(expected, index_of_marker_in_bytes(input.as_bytes(), window));
+            }
+        }
+        assert_eq!(None, index_of_marker_in_bytes(&[0, 1, 0, 1], 3));
+        assert_eq!(Some(3), index_of_marker_in_bytes(&[0xff, 0xff, 0x00], 2));
+    }
+
+    #[test]
+    fn finds_all_markers() {
+        let buffer = CountingRingBuffer::with_limit(3);
+        let markers = buffer.markers("abcabba".chars()).collect::<Vec<_>>();
+        assert_eq!(vec![3, 4, 5], markers);
+
+        let buffer = CountingRingBuffer::with_limit(3);
+        let markers = buffer.markers("abcdefg".chars()).non_overlapping();
+        assert_eq!(vec![3, 6], markers.collect::<Vec<_>>());
+
+        let buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(4);
+        assert_eq!(0, buffer.markers("aaaaaaa".chars()).count());
+    }
+
+    #[test]
+    fn finds_markers_with_at_least_k_distinct_items() {
+        let buffer = CountingRingBuffer::with_limit(4);
+        let markers = buffer
+            .markers([1, 1, 2, 3, 3, 3, 3, 4])
+            .with_min_distinct(3);
+        assert_eq!(vec![4, 5], markers.collect::<Vec<_>>());
+
+        let buffer = CountingRingBuffer::with_limit(3);
+        let markers = buffer
+            .markers("aabbccdd".chars())
+            .with_min_distinct(2)
+            .non_overlapping();
+        assert_eq!(vec![3, 6], markers.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn determines_marker_spanning_chunks_of_a_reader() {
+        let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
+        for chunk_size in [1, 2, 3, 7, 64] {
+            let index = index_of_marker_in_reader(input.as_bytes(), 4, chunk_size).unwrap();
+            assert_eq!(Some(10), index);
+            let index = index_of_marker_in_reader(input.as_bytes(), 14, chunk_size).unwrap();
+            assert_eq!(Some(29), index);
+        }
+
+        let index = index_of_marker_in_reader("aaaa".as_bytes(), 2, 2).unwrap();
+        assert_eq!(None, index);
+    }
+
+    #[test]
+    fn determines_markers_for_several_windows_in_one_pass() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        let markers = markers_in_reader(input.as_bytes(), &[14, 4, 40, 1], 5).unwrap();
+        assert_eq!(vec![Some(19), Some(7), None, Some(1)], markers);
+
+        let markers = markers_in_reader(input.as_bytes(), &[4, 4], 5).unwrap();
+        assert_eq!(vec![Some(7), Some(7)], markers);
+    }
+
+    #[test]
+    fn reports_markers_before_the_stream_ends() {
+        // A live stream that breaks off before a marker of size 14 ever shows up.
+        let mut reader = "abcd".as_bytes().chain(Broken);
+        let mut found = vec![];
+        let result = scan_reader(&mut reader, &[14, 4], 2, |window, index| {
+            found.push((window, index))
+        });
+        assert!(result.is_err());
+        assert_eq!(vec![(4, 4)], found);
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("capture stopped"))
+        }
+    }
+
+    #[test]
+    fn stops_reading_once_marker_was_found() {
+        let mut reader = "abcd".as_bytes().chain(std::io::repeat(b'x'));
+        let index = index_of_marker_in_reader(&mut reader, 4, 2).unwrap();
+        assert_eq!(Some(4), index);
+    }
+
+    /// Random lowercase strings over a small alphabet, so markers show up at varying positions.
+    fn random_streams() -> Vec<String> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..50)
+            .map(|_| {
+                let len = (next() % 400) as usize;
+                let alphabet = 2 + (next() % 20) as u8;
+                (0..len)
+                    .map(|_| (b'a' + (next() % alphabet as u64) as u8) as char)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn uses_at_most_one_chunk_per_thread() {
+        for (len, threads) in [(127, 64), (400, 8), (10, 3), (3, 8), (0, 4)] {
+            let input = "ab".repeat(len).chars().take(len).collect::<String>();
+            let chunks = chunk_ranges(&input, 4, threads);
+            assert!(chunks.len() <= threads, "{len} {threads}");
+            assert!(chunks.windows(2).all(|pair| pair[0].1 >= pair[1].0));
+            assert!(chunks.iter().all(|&(_, end)| end <= len));
+        }
+        assert_eq!(vec![(0, 4), (3, 5)], chunk_ranges("abcde", 2, 2));
+        // Chunks never start or end within a char.
+        assert_eq!(vec![(0, 5), (4, 5)], chunk_ranges("ääa", 2, 2));
+    }
+
+    #[test]
+    fn searches_large_inputs_in_parallel() {
+        for input in [
+            "ab".repeat(PARALLEL_THRESHOLD) + "cd",
+            "abcd".to_string() + &"a".repeat(PARALLEL_THRESHOLD),
+            "ä".repeat(PARALLEL_THRESHOLD / 2),
+        ] {
+            let mut parallel = CountingRingBuffer::with_limit(4);
+            let mut sequential = CountingRingBuffer::with_limit(4);
+            assert_eq!(
+                sequential.index_of_marker(input.chars()),
+                parallel.index_of_market(&input)
+            );
+            assert_eq!(
+                sequential.iter().collect::<Vec<_>>(),
+                parallel.iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    fn buffer_for(window: usize) -> CountingRingBuffer<char> {
+        CountingRingBuffer::with_limit(window)
+    }
+
+    #[test]
+    fn parallel_search_matches_sequential_search() {
+        for input in random_streams() {
+            for window in [1, 2, 4, 9, 14] {
+                let mut buffer: CountingRingBuffer<char> = CountingRingBuffer::with_limit(window);
+                let expected = buffer.index_of_market(&input);
+                let wide = input.replace('a', "ä").replace('b', "😀");
+                let wide_expected = buffer_for(window).index_of_market(&wide);
+                for threads in [1, 2, 3, 8] {
+                    let actual = par_index_of_market(&input, window, threads);
+                    assert_eq!(expected, actual, "{input} {window} {threads}");
+                    let actual = par_index_of_market(&wide, window, threads);
+                    assert_eq!(wide_expected, actual, "{wide} {window} {threads}");
+                }
             }
         }
     }
@@ -114,76 +269,323 @@ pub(crate) fn solve() {
     println!("--------------------------------------");
     println!("Day 6");
     println!("--------------------------------------");
-    let input = std::fs::read_to_string("../input/day6.txt").expect("Input file is missing");
-
-    let mut transmission_buffer: Buffer<char> = Buffer::with_limit(4);
-    let index = transmission_buffer.index_of_market(input.as_str());
-    println!("Day 6 Part 1: {}", index.unwrap());
+    let input = File::open("../input/day6.txt").expect("Input file is missing");
 
-    let mut transmission_buffer: Buffer<char> = Buffer::with_limit(14);
-    let index = transmission_buffer.index_of_market(input.as_str());
-    println!("Day 6 Part 2: {}", index.unwrap());
+    let markers = markers_in_reader(input, &[4, 14], CHUNK_SIZE).unwrap();
+    println!("Day 6 Part 1: {}", markers[0].unwrap());
+    println!("Day 6 Part 2: {}", markers[1].unwrap());
 }
 
-#[derive(Debug)]
-struct Buffer<T> {
-    deque: VecDeque<T>,
-    capacity: usize,
+/// Scans stdin for the first marker of each given size, e.g. to search a live capture. Each marker
+/// is printed as soon as it appears.
+pub(crate) fn solve_stdin(windows: &[usize]) {
+    let stdin = std::io::stdin().lock();
+    let markers = scan_reader(stdin, windows, CHUNK_SIZE, |window, index| {
+        println!("Marker of size {window} after byte {index}")
+    })
+    .expect("Can't read stdin");
+    for (window, marker) in windows.iter().zip(markers) {
+        if marker.is_none() {
+            println!("No marker of size {window} found");
+        }
+    }
 }
 
-impl<T> Buffer<T>
+impl<T> CountingRingBuffer<T>
 where
-    T: Eq + Hash,
+    T: Eq + Hash + Clone,
 {
-    fn is_at_capacity(&self) -> bool {
-        self.deque.len() == self.capacity
+    /// Returns the number of items read once the last `capacity` of them are all distinct.
+    fn index_of_marker<I>(&mut self, items: I) -> Option<usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for (i, item) in items.into_iter().enumerate() {
+            self.add(item);
+            if self.is_at_capacity() && self.are_items_distinct() {
+                return Some(i + 1);
+            }
+        }
+        None
     }
+}
 
-    fn are_items_distinct(&self) -> bool {
-        self.deque.len() == self.deque.iter().count_unique()
+impl<T> CountingRingBuffer<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Iterates over the end of every full window whose items are all distinct.
+    fn markers<I>(self, items: I) -> Markers<T, I::IntoIter>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Markers {
+            min_distinct: self.capacity(),
+            buffer: self,
+            items: items.into_iter().enumerate(),
+            restart: false,
+        }
     }
+}
 
-    fn with_limit(capacity: usize) -> Self {
-        Buffer {
-            deque: VecDeque::from(vec![]),
-            capacity,
+struct Markers<T, I> {
+    buffer: CountingRingBuffer<T>,
+    items: Enumerate<I>,
+    min_distinct: usize,
+    restart: bool,
+}
+
+impl<T, I> Markers<T, I> {
+    /// Starts looking for the next marker from scratch after each one that was found, so the
+    /// windows of two markers never overlap.
+    fn non_overlapping(self) -> Self {
+        Markers {
+            restart: true,
+            ..self
         }
     }
 
-    fn add(&mut self, e: T) {
-        self.deque.push_back(e);
-        if self.len() > self.capacity {
-            self.deque.pop_front();
+    /// Treats a window as marker as soon as it contains `min_distinct` distinct items.
+    fn with_min_distinct(self, min_distinct: usize) -> Self {
+        Markers {
+            min_distinct,
+            ..self
         }
     }
+}
+
+impl<T, I> Iterator for Markers<T, I>
+where
+    T: Eq + Hash + Clone,
+    I: Iterator<Item = T>,
+{
+    type Item = usize;
 
-    fn len(&self) -> usize {
-        self.deque.len()
+    fn next(&mut self) -> Option<Self::Item> {
+        for (i, item) in &mut self.items {
+            self.buffer.add(item);
+            if self.buffer.is_at_capacity() && self.buffer.distinct_count() >= self.min_distinct {
+                if self.restart {
+                    self.buffer.clear();
+                }
+                return Some(i + 1);
+            }
+        }
+        None
     }
 }
 
-impl Buffer<char> {
+/// Inputs at least this long are searched on several threads when possible.
+const PARALLEL_THRESHOLD: usize = 1 << 20;
+
+/// How many chars a thread reads between checks whether an earlier chunk already has a marker.
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+impl CountingRingBuffer<char> {
+    /// Large inputs are searched in parallel if the buffer starts out empty. Afterwards the buffer
+    /// holds the same items as after a sequential search.
     fn index_of_market(&mut self, input: &str) -> Option<usize> {
-        for (i, c) in input.chars().enumerate() {
-            self.add(c);
-            if self.is_at_capacity() && self.are_items_distinct() {
-                return Some(i + 1);
+        if input.len() < PARALLEL_THRESHOLD || !self.is_empty() {
+            return self.index_of_marker(input.chars());
+        }
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        let end = par_marker_end(input, self.capacity(), threads);
+        let read = &input[..end.unwrap_or(input.len())];
+        let window = read.chars().rev().take(self.capacity()).collect::<Vec<_>>();
+        self.extend(window.into_iter().rev());
+        end.map(|_| read.chars().count())
+    }
+}
+
+/// Same as `CountingRingBuffer::index_of_market`, but searches chunks of `input` in parallel.
+fn par_index_of_market(input: &str, window: usize, threads: usize) -> Option<usize> {
+    par_marker_end(input, window, threads).map(|end| input[..end].chars().count())
+}
+
+/// Byte offset right after the first marker, found by searching one chunk per thread. Chunks
+/// overlap by `window - 1` chars, so every window lies within one chunk, and a marker in an earlier
+/// chunk always ends before any marker of a later one. Threads therefore give up as soon as an
+/// earlier chunk has a marker.
+fn par_marker_end(input: &str, window: usize, threads: usize) -> Option<usize> {
+    // Index of the earliest chunk that contains a marker so far.
+    let found = AtomicUsize::new(usize::MAX);
+    thread::scope(|scope| {
+        let handles = chunk_ranges(input, window, threads)
+            .into_iter()
+            .enumerate()
+            .map(|(chunk, (start, end))| {
+                let found = &found;
+                let items = &input[start..end];
+                scope.spawn(move || {
+                    let mut buffer = CountingRingBuffer::with_limit(window);
+                    for (i, (offset, item)) in items.char_indices().enumerate() {
+                        if i % CANCEL_CHECK_INTERVAL == 0 && found.load(Ordering::Relaxed) < chunk {
+                            return None;
+                        }
+                        buffer.add(item);
+                        if buffer.is_at_capacity() && buffer.are_items_distinct() {
+                            found.fetch_min(chunk, Ordering::Relaxed);
+                            return Some(start + offset + item.len_utf8());
+                        }
+                    }
+                    None
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .min()
+    })
+}
+
+/// Splits `input` into at most `threads` byte ranges that start on char boundaries, each extended
+/// by the `window - 1` chars that start the next one.
+fn chunk_ranges(input: &str, window: usize, threads: usize) -> Vec<(usize, usize)> {
+    let chunk_size = input.len().div_ceil(threads.max(1)).max(1);
+    let mut ranges = vec![];
+    let mut start = 0;
+    while start < input.len() {
+        let mut next = (start + chunk_size).min(input.len());
+        while !input.is_char_boundary(next) {
+            next += 1;
+        }
+        let end = input[next..]
+            .char_indices()
+            .nth(window.saturating_sub(1))
+            .map_or(input.len(), |(offset, _)| next + offset);
+        ranges.push((start, end));
+        start = next;
+    }
+    ranges
+}
+
+/// Same as `CountingRingBuffer::index_of_marker`, but for bytes. Instead of counting items it
+/// remembers where each byte was last seen, which gives the longest run of distinct bytes so far.
+/// A marker of any size appears as soon as that run is long enough, so several sizes can be
+/// searched at once.
+///
+/// Bytes can be fed in chunks, a window may span several of them.
+struct ByteMarkerDetector {
+    windows: Vec<usize>,
+    markers: Vec<Option<usize>>,
+    pending: Vec<usize>,
+    last_seen: [Option<usize>; 256],
+    start: usize,
+    offset: usize,
+}
+
+impl ByteMarkerDetector {
+    fn with_windows(windows: &[usize]) -> Self {
+        // Indices of the windows that still lack a marker, largest window first.
+        let pending = (0..windows.len())
+            .sorted_by_key(|&i| Reverse(windows[i]))
+            .collect();
+        ByteMarkerDetector {
+            windows: windows.to_vec(),
+            markers: vec![None; windows.len()],
+            pending,
+            last_seen: [None; 256],
+            start: 0,
+            offset: 0,
+        }
+    }
+
+    fn with_window(window: usize) -> Self {
+        ByteMarkerDetector::with_windows(&[window])
+    }
+
+    /// Processes the given chunk until a marker was found for every window. Returns the markers
+    /// found within this chunk as `(window, index)` pairs, in the order they appeared.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<(usize, usize)> {
+        let mut found = vec![];
+        for &byte in chunk {
+            if self.is_done() {
+                break;
+            }
+            let i = self.offset;
+            self.offset += 1;
+            if let Some(previous) = self.last_seen[byte as usize] {
+                self.start = self.start.max(previous + 1);
+            }
+            self.last_seen[byte as usize] = Some(i);
+
+            // The run grows by one byte at most, so each window is hit exactly at its size.
+            while let Some(&next) = self.pending.last() {
+                if i + 1 - self.start < self.windows[next] {
+                    break;
+                }
+                self.markers[next] = Some(i + 1);
+                self.pending.pop();
+                found.push((self.windows[next], i + 1));
             }
         }
-        None
+        found
+    }
+
+    /// Whether a marker was found for every window.
+    fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The first marker for each window, in the order the windows were given.
+    fn markers(&self) -> &[Option<usize>] {
+        &self.markers
     }
 }
 
-trait CountUnique {
-    fn count_unique(self) -> usize;
+fn index_of_marker_in_bytes(bytes: &[u8], window: usize) -> Option<usize> {
+    let mut detector = ByteMarkerDetector::with_window(window);
+    detector.feed(bytes);
+    detector.markers()[0]
+}
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Reads `reader` in chunks of `chunk_size` bytes and stops as soon as the marker was found, so
+/// memory use doesn't depend on the size of the input.
+fn index_of_marker_in_reader<R: Read>(
+    reader: R,
+    window: usize,
+    chunk_size: usize,
+) -> io::Result<Option<usize>> {
+    Ok(markers_in_reader(reader, &[window], chunk_size)?[0])
+}
+
+/// Like `index_of_marker_in_reader`, but finds the first marker for each of the given window
+/// sizes in a single pass.
+fn markers_in_reader<R: Read>(
+    reader: R,
+    windows: &[usize],
+    chunk_size: usize,
+) -> io::Result<Vec<Option<usize>>> {
+    scan_reader(reader, windows, chunk_size, |_, _| {})
 }
 
-impl<I, T> CountUnique for I
+/// Like `markers_in_reader`, but calls `on_marker` with the window and index of each marker as
+/// soon as the chunk containing it was read, so live streams don't hold back earlier markers.
+fn scan_reader<R, F>(
+    mut reader: R,
+    windows: &[usize],
+    chunk_size: usize,
+    mut on_marker: F,
+) -> io::Result<Vec<Option<usize>>>
 where
-    I: Iterator<Item = T>,
-    T: Eq + Hash,
+    R: Read,
+    F: FnMut(usize, usize),
 {
-    fn count_unique(self) -> usize {
-        self.collect::<HashSet<_>>().len()
+    let mut detector = ByteMarkerDetector::with_windows(windows);
+    let mut chunk = vec![0; chunk_size];
+    while !detector.is_done() {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for (window, index) in detector.feed(&chunk[..read]) {
+            on_marker(window, index);
+        }
     }
+    Ok(detector.markers().to_vec())
 }
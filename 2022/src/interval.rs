@@ -0,0 +1,125 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::interval::Interval;
+
+    #[test]
+    fn parses_interval() {
+        let interval = Interval::from_str("2-4").unwrap();
+        assert_eq!(Interval::new(2, 4), interval);
+
+        let interval = Interval::from_str("1-9000000000").unwrap();
+        assert_eq!(9_000_000_000, interval.len());
+        assert_eq!(1 << 64, Interval::new(i64::MIN, i64::MAX).len());
+
+        assert!(Interval::from_str("4-2").is_err());
+        assert!(Interval::from_str("4").is_err());
+        assert!(Interval::from_str("a-b").is_err());
+    }
+
+    #[test]
+    fn determines_containment() {
+        let outer = Interval::new(2, 8);
+        assert!(outer.contains(&Interval::new(3, 7)));
+        assert!(outer.contains(&outer));
+        assert!(!outer.contains(&Interval::new(6, 9)));
+        assert!(outer.contains_section(8));
+        assert!(!outer.contains_section(9));
+    }
+
+    #[test]
+    fn determines_intersection_and_adjacency() {
+        let interval = Interval::new(5, 7);
+        assert_eq!(
+            Some(Interval::new(6, 7)),
+            interval.intersection(&Interval::new(6, 9))
+        );
+        assert_eq!(None, interval.intersection(&Interval::new(8, 9)));
+        assert!(interval.is_adjacent_to(&Interval::new(8, 9)));
+        assert!(interval.is_adjacent_to(&Interval::new(1, 4)));
+        assert!(!interval.is_adjacent_to(&Interval::new(7, 9)));
+        assert!(!Interval::new(1, i64::MAX).is_adjacent_to(&Interval::new(i64::MIN, -1)));
+    }
+
+    #[test]
+    fn determines_overlap() {
+        let interval = Interval::new(5, 7);
+        assert!(interval.overlaps(&Interval::new(7, 9)));
+        assert!(interval.overlaps(&Interval::new(1, 5)));
+        assert!(!interval.overlaps(&Interval::new(8, 9)));
+        assert!(!interval.overlaps(&Interval::new(1, 4)));
+    }
+}
+
+/// An inclusive range of section IDs.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub(crate) struct Interval {
+    pub(crate) start: i64,
+    pub(crate) end: i64,
+}
+
+impl Interval {
+    pub(crate) fn new(start: i64, end: i64) -> Self {
+        assert!(start <= end, "Interval must not end before it starts");
+        Interval { start, end }
+    }
+
+    /// Number of sections in the interval. Doesn't fit in a `u64` for the full `i64` range.
+    pub(crate) fn len(&self) -> u128 {
+        self.start.abs_diff(self.end) as u128 + 1
+    }
+
+    pub(crate) fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub(crate) fn contains_section(&self, section: i64) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    pub(crate) fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// The sections both intervals have in common.
+    pub(crate) fn intersection(&self, other: &Interval) -> Option<Interval> {
+        if self.overlaps(other) {
+            Some(Interval::new(
+                self.start.max(other.start),
+                self.end.min(other.end),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Whether `other` starts right after this interval ends or the other way around.
+    pub(crate) fn is_adjacent_to(&self, other: &Interval) -> bool {
+        self.end.checked_add(1) == Some(other.start) || other.end.checked_add(1) == Some(self.start)
+    }
+}
+
+impl FromStr for Interval {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once('-').ok_or("Interval is missing a '-'")?;
+        let start: i64 = start.parse().map_err(|_| "Can't parse start of interval")?;
+        let end: i64 = end.parse().map_err(|_| "Can't parse end of interval")?;
+        if start > end {
+            return Err("Interval must not end before it starts");
+        }
+        Ok(Interval::new(start, end))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
@@ -0,0 +1,257 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::interval::Interval;
+
+#[cfg(test)]
+mod tests {
+    use crate::interval::Interval;
+    use crate::interval_set::{covered_at_least, intervals_from_lines, IntervalSet};
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        IntervalSet::from_intervals(
+            intervals
+                .iter()
+                .map(|&(start, end)| Interval::new(start, end)),
+        )
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_intervals() {
+        let merged = set(&[(6, 8), (1, 2), (3, 4), (7, 10), (12, 12)]);
+        assert_eq!(
+            vec![
+                Interval::new(1, 4),
+                Interval::new(6, 10),
+                Interval::new(12, 12)
+            ],
+            merged.intervals
+        );
+        assert_eq!(10, merged.len());
+    }
+
+    #[test]
+    fn determines_if_section_is_contained() {
+        let set = set(&[(1, 4), (6, 10)]);
+        assert!(set.contains_section(1));
+        assert!(set.contains_section(7));
+        assert!(!set.contains_section(5));
+        assert!(!set.contains_section(11));
+    }
+
+    #[test]
+    fn builds_union() {
+        let union = set(&[(1, 3), (10, 12)]).union(&set(&[(2, 5), (7, 8)]));
+        assert_eq!(set(&[(1, 5), (7, 8), (10, 12)]), union);
+    }
+
+    #[test]
+    fn builds_intersection() {
+        let intersection = set(&[(1, 5), (8, 12)]).intersection(&set(&[(4, 9), (11, 20)]));
+        assert_eq!(set(&[(4, 5), (8, 9), (11, 12)]), intersection);
+        assert!(set(&[(1, 2)]).intersection(&set(&[(3, 4)])).is_empty());
+    }
+
+    #[test]
+    fn builds_difference() {
+        let difference = set(&[(1, 10)]).difference(&set(&[(3, 4), (8, 12)]));
+        assert_eq!(set(&[(1, 2), (5, 7)]), difference);
+    }
+
+    #[test]
+    fn builds_complement_within_bounds() {
+        let complement = set(&[(3, 4), (8, 12)]).complement(Interval::new(1, 10));
+        assert_eq!(set(&[(1, 2), (5, 7)]), complement);
+        assert!(set(&[(1, 10)]).complement(Interval::new(2, 9)).is_empty());
+
+        let complement = IntervalSet::default().complement(Interval::new(i64::MIN, i64::MAX));
+        assert_eq!(set(&[(i64::MIN, i64::MAX)]), complement);
+        assert_eq!(1 << 64, complement.len());
+    }
+
+    #[test]
+    fn finds_sections_covered_at_least_k_times() {
+        let intervals = [
+            Interval::new(1, 5),
+            Interval::new(3, 8),
+            Interval::new(4, 6),
+            Interval::new(9, 9),
+        ];
+        assert_eq!(set(&[(4, 5)]), covered_at_least(&intervals, 3));
+        assert_eq!(set(&[(3, 6)]), covered_at_least(&intervals, 2));
+        assert_eq!(set(&[(1, 9)]), covered_at_least(&intervals, 1));
+    }
+
+    #[test]
+    fn parses_assignment_lines() {
+        let intervals = intervals_from_lines("2-4,6-8\n2-3,4-5\n").unwrap();
+        assert_eq!(4, intervals.len());
+        assert_eq!(set(&[(2, 8)]), IntervalSet::from_intervals(intervals));
+        assert!(intervals_from_lines("2-4").is_err());
+    }
+}
+
+/// A set of sections, kept as sorted intervals that neither overlap nor touch each other.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub(crate) struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub(crate) fn from_intervals<I>(intervals: I) -> Self
+    where
+        I: IntoIterator<Item = Interval>,
+    {
+        let mut merged: Vec<Interval> = vec![];
+        for interval in intervals
+            .into_iter()
+            .sorted_by_key(|interval| interval.start)
+        {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end.saturating_add(1) => {
+                    last.end = last.end.max(interval.end);
+                }
+                _ => merged.push(interval),
+            }
+        }
+        IntervalSet { intervals: merged }
+    }
+
+    pub(crate) fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of sections in the set.
+    pub(crate) fn len(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// The smallest interval containing every section of the set.
+    pub(crate) fn bounds(&self) -> Option<Interval> {
+        let first = self.intervals.first()?;
+        let last = self.intervals.last()?;
+        Some(Interval::new(first.start, last.end))
+    }
+
+    pub(crate) fn contains_section(&self, section: i64) -> bool {
+        self.intervals
+            .binary_search_by(|interval| {
+                if interval.end < section {
+                    Ordering::Less
+                } else if interval.start > section {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub(crate) fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_intervals(self.intervals.iter().chain(&other.intervals).copied())
+    }
+
+    pub(crate) fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            intervals.extend(a.intersection(&b));
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    pub(crate) fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        match self.bounds() {
+            Some(bounds) => self.intersection(&other.complement(bounds)),
+            None => IntervalSet::default(),
+        }
+    }
+
+    /// All sections within `bounds` that aren't part of the set.
+    pub(crate) fn complement(&self, bounds: Interval) -> IntervalSet {
+        let mut intervals = vec![];
+        // The next section that may still be missing, `None` once `bounds` is exhausted.
+        let mut next = Some(bounds.start);
+        for interval in &self.intervals {
+            let Some(start) = next else {
+                break;
+            };
+            if interval.end < start {
+                continue;
+            }
+            if interval.start > bounds.end {
+                break;
+            }
+            if interval.start > start {
+                intervals.push(Interval::new(start, interval.start - 1));
+            }
+            next = interval.end.checked_add(1);
+        }
+        if let Some(start) = next {
+            if start <= bounds.end {
+                intervals.push(Interval::new(start, bounds.end));
+            }
+        }
+        IntervalSet { intervals }
+    }
+}
+
+impl Display for IntervalSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        write!(f, "{}", self.intervals.iter().join(", "))
+    }
+}
+
+/// All sections that at least `k` of the given intervals cover.
+pub(crate) fn covered_at_least(intervals: &[Interval], k: usize) -> IntervalSet {
+    // Coverage changes at the start of an interval and right after its end. Widening to i128
+    // leaves room for the section after `i64::MAX`.
+    let events = intervals
+        .iter()
+        .flat_map(|interval| [(interval.start as i128, 1), (interval.end as i128 + 1, -1)])
+        .sorted();
+
+    let mut result = vec![];
+    let mut coverage: i64 = 0;
+    let mut covered_since = None;
+    for (position, change) in events {
+        coverage += change;
+        match covered_since {
+            None if coverage >= k as i64 => covered_since = Some(position),
+            Some(start) if coverage < k as i64 => {
+                result.push(Interval::new(start as i64, (position - 1) as i64));
+                covered_since = None;
+            }
+            _ => {}
+        }
+    }
+    IntervalSet::from_intervals(result)
+}
+
+/// Parses `a-b,c-d` lines into the intervals of every elf.
+pub(crate) fn intervals_from_lines(input: &str) -> Result<Vec<Interval>, &'static str> {
+    let mut intervals = vec![];
+    for line in input.lines() {
+        let (first, second) = line.split_once(',').ok_or("Line is missing a ','")?;
+        intervals.push(Interval::from_str(first)?);
+        intervals.push(Interval::from_str(second)?);
+    }
+    Ok(intervals)
+}
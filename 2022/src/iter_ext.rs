@@ -0,0 +1,126 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::iter_ext::IterExt;
+
+    #[test]
+    fn counts_unique_items() {
+        assert_eq!(3, [1, 2, 2, 3, 1].into_iter().count_unique());
+        assert_eq!(0, Vec::<char>::new().into_iter().count_unique());
+    }
+
+    #[test]
+    fn finds_most_common_items() {
+        let most_common = "abacabd".chars().most_common(2);
+        assert_eq!(vec![('a', 3), ('b', 2)], most_common);
+
+        // Ties keep the order in which the items first appeared.
+        let most_common = "dcba".chars().most_common(3);
+        assert_eq!(vec![('d', 1), ('c', 1), ('b', 1)], most_common);
+    }
+
+    #[test]
+    fn finds_top_n_items() {
+        let top = [5, 1, 9, 3, 7].into_iter().top_n(3);
+        assert_eq!(vec![9, 7, 5], top);
+
+        let top = [2, 1].into_iter().top_n(3);
+        assert_eq!(vec![2, 1], top);
+        assert!([2, 1].into_iter().top_n(0).is_empty());
+    }
+
+    #[test]
+    fn intersects_many_iterables() {
+        let lines = [
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+        ];
+        let common = lines.iter().map(|line| line.chars()).intersect_all();
+        assert_eq!(HashSet::from(['r', 's', 'f', 'F', 'M']), common);
+
+        let common = [vec![1, 2], vec![2, 3], vec![3, 4]]
+            .into_iter()
+            .intersect_all();
+        assert!(common.is_empty());
+
+        let common = Vec::<Vec<i32>>::new().into_iter().intersect_all();
+        assert!(common.is_empty());
+    }
+}
+
+/// Helpers for iterators that we keep needing across days and that itertools doesn't provide.
+pub(crate) trait IterExt: Iterator {
+    fn count_unique(self) -> usize
+    where
+        Self: Sized,
+        Self::Item: Eq + Hash,
+    {
+        self.collect::<HashSet<_>>().len()
+    }
+
+    /// The `n` most frequent items with their counts, most frequent first. Ties keep the order in
+    /// which the items first appeared.
+    fn most_common(self, n: usize) -> Vec<(Self::Item, usize)>
+    where
+        Self: Sized,
+        Self::Item: Eq + Hash + Clone,
+    {
+        let mut first_seen = HashMap::new();
+        let mut counts = HashMap::new();
+        for (i, item) in self.enumerate() {
+            first_seen.entry(item.clone()).or_insert(i);
+            *counts.entry(item).or_insert(0) += 1;
+        }
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_by_key(|(item, count)| (Reverse(*count), first_seen[item]));
+        counts.truncate(n);
+        counts
+    }
+
+    /// The `n` largest items, largest first. Only ever keeps `n` items around.
+    fn top_n(self, n: usize) -> Vec<Self::Item>
+    where
+        Self: Sized,
+        Self::Item: Ord,
+    {
+        let mut heap = BinaryHeap::with_capacity(n + 1);
+        for item in self {
+            heap.push(Reverse(item));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect()
+    }
+
+    /// The items contained in every one of the iterables.
+    fn intersect_all<T>(mut self) -> HashSet<T>
+    where
+        Self: Sized,
+        Self::Item: IntoIterator<Item = T>,
+        T: Eq + Hash,
+    {
+        let Some(first) = self.next() else {
+            return HashSet::new();
+        };
+        let mut common = first.into_iter().collect::<HashSet<_>>();
+        for other in self {
+            if common.is_empty() {
+                break;
+            }
+            let other = other.into_iter().collect::<HashSet<_>>();
+            common.retain(|item| other.contains(item));
+        }
+        common
+    }
+}
+
+impl<I: Iterator> IterExt for I {}
@@ -7,8 +7,21 @@ mod day4;
 mod day5;
 mod day6;
 mod day7;
+mod interval;
+mod interval_set;
+mod iter_ext;
+mod ring_buffer;
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--day2-report") {
+        day2::report();
+        return;
+    }
+    if std::env::args().any(|arg| arg == "--day6-stdin") {
+        day6::solve_stdin(&[4, 14]);
+        return;
+    }
+
     // day1::solve();
     // day2::solve();
     // day3::solve();
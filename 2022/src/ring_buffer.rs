@@ -0,0 +1,346 @@
+use std::collections::vec_deque::{Drain, Iter};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::iter::Sum;
+use std::ops::{Deref, Index};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::hash::Hash;
+
+    use crate::ring_buffer::{CountingRingBuffer, RingBuffer};
+
+    #[test]
+    fn can_add_elements_to_buffer() {
+        let mut buffer: RingBuffer<usize> = RingBuffer::with_limit(5);
+        buffer.add(1);
+        assert_eq!(buffer.deque, VecDeque::from([1]));
+        assert_eq!(1, buffer.len());
+    }
+
+    #[test]
+    fn buffer_can_contain_capacity_elements() {
+        let mut buffer = CountingRingBuffer::from(vec!['a', 'b', 'c'], 4);
+        buffer.add('d');
+        assert_eq!(4, buffer.len());
+        buffer.add('e');
+        assert_eq!(4, buffer.len());
+
+        let mut buffer = CountingRingBuffer::from(vec!['a'], 2);
+        buffer.add('d');
+        assert_eq!(2, buffer.len());
+        buffer.add('e');
+        assert_eq!(2, buffer.len());
+    }
+
+    #[test]
+    fn can_determine_if_all_contained_items_are_distinct() {
+        let buffer = CountingRingBuffer::from(vec![1, 1], 4);
+        assert!(!buffer.are_items_distinct());
+
+        let buffer = CountingRingBuffer::from(vec![1, 2], 4);
+        assert!(buffer.are_items_distinct());
+    }
+
+    #[test]
+    fn keeps_track_of_duplicates_when_evicting_items() {
+        let mut buffer = CountingRingBuffer::from(vec![1, 2, 1, 1], 4);
+        assert_eq!(2, buffer.duplicates);
+        buffer.add(3);
+        assert_eq!(1, buffer.duplicates);
+        buffer.add(4);
+        assert_eq!(1, buffer.duplicates);
+        buffer.add(5);
+        assert!(buffer.are_items_distinct());
+        assert_eq!(None, buffer.counts.get(&2));
+    }
+
+    #[test]
+    fn can_determine_if_buffer_it_at_capacity() {
+        let buffer = CountingRingBuffer::from(vec![1, 2, 3], 4);
+        assert!(!buffer.is_at_capacity());
+
+        let buffer = CountingRingBuffer::from(vec![1, 2, 3, 4], 4);
+        assert!(buffer.is_at_capacity());
+    }
+
+    #[test]
+    fn returns_evicted_element() {
+        let mut buffer = RingBuffer::with_limit(2);
+        assert_eq!(None, buffer.add('a'));
+        assert_eq!(None, buffer.add('b'));
+        assert_eq!(Some('a'), buffer.add('c'));
+
+        let mut buffer = CountingRingBuffer::with_limit(1);
+        assert_eq!(None, buffer.add('a'));
+        assert_eq!(Some('a'), buffer.add('b'));
+    }
+
+    #[test]
+    fn holds_items_without_hash_or_eq() {
+        let mut buffer = RingBuffer::with_limit(3);
+        buffer.extend([0.5, 1.5, 2.5, 3.5]);
+        assert_eq!(7.5, buffer.sum::<f64>());
+        assert_eq!(vec![1.5], buffer.set_capacity(2));
+        assert_eq!(6.0, buffer.iter().sum::<f64>());
+    }
+
+    #[test]
+    fn can_iterate_and_index_from_oldest_to_newest() {
+        let buffer = CountingRingBuffer::from(vec![1, 2, 3, 4, 5], 3);
+        assert_eq!(vec![&3, &4, &5], buffer.iter().collect::<Vec<_>>());
+        assert_eq!(3, buffer[0]);
+        assert_eq!(5, buffer[2]);
+        assert_eq!(12, (&*buffer).into_iter().sum::<i32>());
+    }
+
+    #[test]
+    fn can_change_capacity() {
+        let mut buffer = CountingRingBuffer::from(vec![1, 1, 2, 3], 4);
+        let evicted = buffer.set_capacity(2);
+        assert_eq!(vec![1, 1], evicted);
+        assert_eq!(vec![&2, &3], buffer.iter().collect::<Vec<_>>());
+        assert!(buffer.are_items_distinct());
+
+        let evicted = buffer.set_capacity(3);
+        assert!(evicted.is_empty());
+        buffer.add(4);
+        assert_eq!(3, buffer.len());
+    }
+
+    #[test]
+    fn can_be_drained() {
+        let mut buffer = CountingRingBuffer::from(vec!['a', 'b', 'a'], 3);
+        assert_eq!(vec!['a', 'b', 'a'], buffer.drain().collect::<Vec<_>>());
+        assert!(buffer.is_empty());
+        assert_eq!(0, buffer.distinct_count());
+        assert!(buffer.are_items_distinct());
+    }
+
+    #[test]
+    fn provides_window_statistics() {
+        let buffer = CountingRingBuffer::from(vec![9, 4, 7, 4, 1], 4);
+        assert_eq!(3, buffer.distinct_count());
+        assert_eq!(2, buffer.count(&4));
+        assert_eq!(0, buffer.count(&9));
+        assert_eq!(Some(&1), buffer.min());
+        assert_eq!(Some(&7), buffer.max());
+        assert_eq!(16, buffer.sum::<i32>());
+    }
+
+    impl<T> CountingRingBuffer<T>
+    where
+        T: Eq + Hash + Clone,
+    {
+        fn from(vec: Vec<T>, capacity: usize) -> Self {
+            let mut buffer = CountingRingBuffer::with_limit(capacity);
+            buffer.extend(vec);
+            buffer
+        }
+    }
+}
+
+/// Keeps the last `capacity` items.
+#[derive(Debug)]
+pub(crate) struct RingBuffer<T> {
+    deque: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub(crate) fn with_limit(capacity: usize) -> Self {
+        RingBuffer {
+            deque: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds `e` as newest item and returns the oldest one if the buffer was at capacity.
+    pub(crate) fn add(&mut self, e: T) -> Option<T> {
+        self.deque.push_back(e);
+        if self.len() > self.capacity {
+            self.deque.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Changes the capacity and returns the oldest items that no longer fit, oldest first.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) -> Vec<T> {
+        self.capacity = capacity;
+        let excess = self.len().saturating_sub(capacity);
+        self.deque.drain(..excess).collect()
+    }
+
+    pub(crate) fn is_at_capacity(&self) -> bool {
+        self.deque.len() == self.capacity
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.deque.is_empty()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Iterates from the oldest to the newest item.
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        self.deque.iter()
+    }
+
+    pub(crate) fn sum<S>(&self) -> S
+    where
+        S: for<'a> Sum<&'a T>,
+    {
+        self.deque.iter().sum()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.deque.clear();
+    }
+
+    pub(crate) fn drain(&mut self) -> Drain<'_, T> {
+        self.deque.drain(..)
+    }
+}
+
+impl<T: Ord> RingBuffer<T> {
+    pub(crate) fn min(&self) -> Option<&T> {
+        self.deque.iter().min()
+    }
+
+    pub(crate) fn max(&self) -> Option<&T> {
+        self.deque.iter().max()
+    }
+}
+
+impl<T> Index<usize> for RingBuffer<T> {
+    type Output = T;
+
+    /// Index `0` is the oldest item.
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.deque[index]
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.add(e);
+        }
+    }
+}
+
+/// A `RingBuffer` that also knows how often each item occurs, so that checking for duplicates
+/// doesn't require looking at every item. Read access goes through the inner buffer.
+#[derive(Debug)]
+pub(crate) struct CountingRingBuffer<T> {
+    buffer: RingBuffer<T>,
+    counts: HashMap<T, usize>,
+    duplicates: usize,
+}
+
+impl<T> CountingRingBuffer<T> {
+    pub(crate) fn are_items_distinct(&self) -> bool {
+        self.duplicates == 0
+    }
+
+    pub(crate) fn distinct_count(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buffer.clear();
+        self.counts.clear();
+        self.duplicates = 0;
+    }
+
+    pub(crate) fn drain(&mut self) -> Drain<'_, T> {
+        self.counts.clear();
+        self.duplicates = 0;
+        self.buffer.drain()
+    }
+}
+
+impl<T> CountingRingBuffer<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn with_limit(capacity: usize) -> Self {
+        CountingRingBuffer {
+            buffer: RingBuffer::with_limit(capacity),
+            counts: HashMap::new(),
+            duplicates: 0,
+        }
+    }
+
+    /// Adds `e` as newest item and returns the oldest one if the buffer was at capacity.
+    pub(crate) fn add(&mut self, e: T) -> Option<T> {
+        let count = self.counts.entry(e.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            self.duplicates += 1;
+        }
+        let evicted = self.buffer.add(e)?;
+        self.forget(&evicted);
+        Some(evicted)
+    }
+
+    /// Changes the capacity and returns the oldest items that no longer fit, oldest first.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) -> Vec<T> {
+        let evicted = self.buffer.set_capacity(capacity);
+        for item in &evicted {
+            self.forget(item);
+        }
+        evicted
+    }
+
+    /// How often `item` occurs in the buffer.
+    pub(crate) fn count(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    fn forget(&mut self, evicted: &T) {
+        let count = self.counts.get_mut(evicted).unwrap();
+        *count -= 1;
+        if *count > 0 {
+            self.duplicates -= 1;
+        } else {
+            self.counts.remove(evicted);
+        }
+    }
+}
+
+impl<T> Deref for CountingRingBuffer<T> {
+    type Target = RingBuffer<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl<T> Extend<T> for CountingRingBuffer<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.add(e);
+        }
+    }
+}